@@ -2,13 +2,13 @@ extern crate nineman;
 
 use nineman::game::Game;
 use nineman::player::Player;
+use nineman::player::PlayerId;
 use nineman::player::Human;
-use nineman::player::Random;
 
 fn main() {
-    let p1 = Player::new(String::from("Dave"), 1, Box::new(Human {player_id: 1}));
+    let p1 = Player::new(String::from("Dave"), PlayerId::One, Box::new(Human::new(PlayerId::One)));
 
-    let p2 = Player::new(String::from("Bertie"), 2, Box::new(Human {player_id: 2}));
+    let p2 = Player::new(String::from("Bertie"), PlayerId::Two, Box::new(Human::new(PlayerId::Two)));
 
     let mut game = Game::new(p1, p2);
 
@@ -0,0 +1,50 @@
+// Algebraic notation for points, as used for chess: a file letter for the column and a rank
+// number for the row, counting up from the bottom left. On the Nine Men's Morris board 0nw is a7,
+// 2se is e3 and so on. Works for any board, as long as it's no more than 26 points wide
+
+use board::BoardTopology;
+
+pub fn square(topology: &BoardTopology, point: usize) -> String {
+    let point = &topology.points()[point];
+    let file = (b'a' + point.x as u8) as char;
+    let rank = topology.height() - point.y;
+    format!("{}{}", file, rank)
+}
+
+// None if square isn't a point on the board
+pub fn parse_square(topology: &BoardTopology, square: &str) -> Option<usize> {
+    let mut chars = square.trim().chars();
+    let file = chars.next().filter(|c| c.is_ascii_lowercase())?;
+    // Only plain digits, as parse would also take a sign, and no leading zeros
+    let rank = chars.as_str();
+    if !rank.bytes().all(|b| b.is_ascii_digit()) || rank.starts_with('0') {
+        return None;
+    }
+    let rank = rank.parse::<i8>().ok()?;
+
+    let x = (file as u8 - b'a') as i8;
+    let y = topology.height() - rank;
+    topology.points().iter().position(|p| p.x == x && p.y == y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use board::{generate_topology, BoardKind};
+
+    #[test]
+    fn squares_match_ids() {
+        let topology = generate_topology(BoardKind::NineMen);
+        let squares = [("0nw", "a7"), ("0se", "g1"), ("1w", "b4"), ("2se", "e3"), ("2s", "d3"), ("0s", "d1")];
+
+        for &(id, expected) in &squares {
+            let point = topology.index_of(id).unwrap();
+            assert_eq!(square(&topology, point), expected);
+            assert_eq!(parse_square(&topology, expected), Some(point));
+        }
+
+        for invalid in &["d4", "h1", "a0", "a", "", "A7", "a-7", "a+7", "a07"] {
+            assert_eq!(parse_square(&topology, invalid), None, "{}", invalid);
+        }
+    }
+}
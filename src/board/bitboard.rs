@@ -0,0 +1,327 @@
+// Compact Nine Men's Morris board for search: one u32 per player with a bit per point.
+// Points are numbered the same way as the built NineMen board: layer * 8 plus the position
+// around that square clockwise from the top left corner (nw, n, ne, e, se, s, sw, w).
+// Everything here is Copy and never allocates, so can be used freely in a search tree.
+
+use std::slice::Iter;
+
+use board::Board;
+use board::Cell;
+use board::BoardKind;
+use board::generate_topology;
+use game::MillRemoval;
+use game::Ply;
+use player::PlayerId;
+
+pub const POINTS: usize = 24;
+pub const MILLS: usize = 16;
+const ALL_POINTS: u32 = (1 << POINTS) - 1;
+// Most moves possible in a position: 12 pieces flying to any of 12 empty points
+pub const MAX_MOVES: usize = 144;
+
+const SQUARE: usize = 8;
+const LAYERS: usize = 3;
+
+pub const ADJACENT: [u32; POINTS] = adjacency();
+pub const MILL_MASKS: [u32; MILLS] = mill_masks();
+
+const fn adjacency() -> [u32; POINTS] {
+    let mut adjacent = [0; POINTS];
+    let mut point = 0;
+    while point < POINTS {
+        let (layer, side) = (point / SQUARE, point % SQUARE);
+        let square = layer * SQUARE;
+
+        adjacent[point] |= 1 << (square + (side + 1) % SQUARE);
+        adjacent[point] |= 1 << (square + (side + SQUARE - 1) % SQUARE);
+
+        // Middles of sides are odd, and connect to the squares either side
+        if side % 2 == 1 {
+            if layer > 0 {
+                adjacent[point] |= 1 << (point - SQUARE);
+            }
+            if layer < LAYERS - 1 {
+                adjacent[point] |= 1 << (point + SQUARE);
+            }
+        }
+        point += 1;
+    }
+    adjacent
+}
+
+const fn mill_masks() -> [u32; MILLS] {
+    let mut mills = [0; MILLS];
+    let mut mill = 0;
+
+    let mut layer = 0;
+    while layer < LAYERS {
+        let square = layer * SQUARE;
+        let mut corner = 0;
+        while corner < SQUARE {
+            mills[mill] = (1 << (square + corner)) | (1 << (square + corner + 1))
+                | (1 << (square + (corner + 2) % SQUARE));
+            mill += 1;
+            corner += 2;
+        }
+        layer += 1;
+    }
+
+    let mut middle = 1;
+    while middle < SQUARE {
+        mills[mill] = (1 << middle) | (1 << (middle + SQUARE)) | (1 << (middle + 2 * SQUARE));
+        mill += 1;
+        middle += 2;
+    }
+
+    mills
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum BitMove {
+    Place { player_id: PlayerId, point: u8 },
+    Move { player_id: PlayerId, from: u8, to: u8 },
+    // Removal of an opponent's piece by player_id after making a mill
+    Remove { player_id: PlayerId, point: u8 },
+}
+
+impl BitMove {
+    pub fn player_id(&self) -> PlayerId {
+        match *self {
+            BitMove::Place{player_id, ..} => player_id,
+            BitMove::Move{player_id, ..} => player_id,
+            BitMove::Remove{player_id, ..} => player_id,
+        }
+    }
+
+    // None for Ply::Root
+    pub fn from_ply(ply: Ply) -> Option<BitMove> {
+        match ply {
+            Ply::Root => None,
+            Ply::Placement { player_id, piece_id } => Some(BitMove::Place { player_id, point: piece_id as u8 }),
+            Ply::Move { player_id, mv: (from, to) } => Some(BitMove::Move { player_id, from: from as u8, to: to as u8 }),
+            Ply::Mill { player_id, piece_id } => Some(BitMove::Remove { player_id, point: piece_id as u8 }),
+        }
+    }
+}
+
+impl From<BitMove> for Ply {
+    fn from(mv: BitMove) -> Ply {
+        match mv {
+            BitMove::Place { player_id, point } => Ply::Placement { player_id, piece_id: point as usize },
+            BitMove::Move { player_id, from, to } => Ply::Move { player_id, mv: (from as usize, to as usize) },
+            BitMove::Remove { player_id, point } => Ply::Mill { player_id, piece_id: point as usize },
+        }
+    }
+}
+
+// Fixed size list of moves, so generating them doesn't allocate
+#[derive(Clone, Copy)]
+pub struct MoveList {
+    moves: [BitMove; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    fn new() -> Self {
+        MoveList { moves: [BitMove::Place { player_id: PlayerId::One, point: 0 }; MAX_MOVES], len: 0 }
+    }
+
+    fn push(&mut self, mv: BitMove) {
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> Iter<'_, BitMove> {
+        self.moves[..self.len].iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a BitMove;
+    type IntoIter = Iter<'a, BitMove>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct BitBoard {
+    // Indexed by PlayerId::index
+    pieces: [u32; 2],
+}
+
+impl BitBoard {
+    pub fn empty_board() -> Self {
+        BitBoard::default()
+    }
+
+    // Only Nine Men's Morris boards can be represented
+    pub fn from_board(board: &Board) -> Option<Self> {
+        if *board.topology() != generate_topology(BoardKind::NineMen) {
+            return None;
+        }
+
+        let mut bit_board = BitBoard::empty_board();
+        for point in 0..POINTS {
+            if let Cell::Piece(player_id) = board.piece_at(point) {
+                bit_board.pieces[player_id.index()] |= 1 << point;
+            }
+        }
+        Some(bit_board)
+    }
+
+    pub fn pieces(&self, player_id: PlayerId) -> u32 {
+        self.pieces[player_id.index()]
+    }
+
+    pub fn count(&self, player_id: PlayerId) -> u32 {
+        self.pieces(player_id).count_ones()
+    }
+
+    pub fn empty(&self) -> u32 {
+        !(self.pieces[0] | self.pieces[1]) & ALL_POINTS
+    }
+
+    pub fn piece_at(&self, point: usize) -> Cell {
+        match PlayerId::both().iter().find(|p| self.pieces(**p) & (1 << point) != 0) {
+            Some(player_id) => Cell::Piece(*player_id),
+            None => Cell::Empty,
+        }
+    }
+
+    pub fn placements(&self, player_id: PlayerId) -> MoveList {
+        let mut moves = MoveList::new();
+        for point in bits(self.empty()) {
+            moves.push(BitMove::Place { player_id, point });
+        }
+        moves
+    }
+
+    // Flying pieces can land on any empty point, otherwise only on an ADJACENT one
+    pub fn moves(&self, player_id: PlayerId, flying: bool) -> MoveList {
+        let mut moves = MoveList::new();
+        let empty = self.empty();
+        for from in bits(self.pieces(player_id)) {
+            let destinations = if flying { empty } else { ADJACENT[from as usize] & empty };
+            for to in bits(destinations) {
+                moves.push(BitMove::Move { player_id, from, to });
+            }
+        }
+        moves
+    }
+
+    // Opponent pieces player_id can remove after making a mill
+    pub fn removals(&self, player_id: PlayerId, removal: MillRemoval) -> MoveList {
+        let opponent = self.pieces(player_id.other());
+        let not_in_mills = opponent & !self.in_mills(player_id.other());
+
+        let removable = match removal {
+            MillRemoval::FromMillsIfNoOther if not_in_mills == 0 => opponent,
+            MillRemoval::FromMillsIfNoOther | MillRemoval::NotFromMills => not_in_mills,
+            MillRemoval::AnyPiece => opponent,
+        };
+
+        let mut moves = MoveList::new();
+        for point in bits(removable) {
+            moves.push(BitMove::Remove { player_id, point });
+        }
+        moves
+    }
+
+    // Mask of player_id's pieces that are in mills
+    pub fn in_mills(&self, player_id: PlayerId) -> u32 {
+        let pieces = self.pieces(player_id);
+        MILL_MASKS.iter()
+            .filter(|m| pieces & **m == **m)
+            .fold(0, |in_mills, m| in_mills | m)
+    }
+
+    // Whether the piece at point is part of a mill for player_id, i.e. a placement or move to point made one
+    pub fn forms_mill(&self, player_id: PlayerId, point: usize) -> bool {
+        let pieces = self.pieces(player_id);
+        MILL_MASKS.iter().any(|m| m & (1 << point) != 0 && pieces & *m == *m)
+    }
+
+    pub fn make(&mut self, mv: BitMove) {
+        match mv {
+            BitMove::Place { player_id, point } => self.pieces[player_id.index()] |= 1 << point,
+            BitMove::Move { player_id, from, to } => self.pieces[player_id.index()] ^= (1 << from) | (1 << to),
+            BitMove::Remove { player_id, point } =>
+                self.pieces[player_id.other().index()] &= !(1 << point),
+        }
+    }
+
+    pub fn unmake(&mut self, mv: BitMove) {
+        match mv {
+            BitMove::Place { player_id, point } => self.pieces[player_id.index()] &= !(1 << point),
+            BitMove::Move { player_id, from, to } => self.pieces[player_id.index()] ^= (1 << from) | (1 << to),
+            BitMove::Remove { player_id, point } =>
+                self.pieces[player_id.other().index()] |= 1 << point,
+        }
+    }
+}
+
+// Indices of the set bits in mask, lowest first
+fn bits(mut mask: u32) -> impl Iterator<Item = u8> {
+    ::std::iter::from_fn(move || {
+        if mask == 0 {
+            None
+        } else {
+            let bit = mask.trailing_zeros() as u8;
+            mask &= mask - 1;
+            Some(bit)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_match_built_board() {
+        let topology = generate_topology(BoardKind::NineMen);
+
+        for (point, adjacent) in ADJACENT.iter().enumerate() {
+            let connections = topology.connections(point).iter().fold(0, |mask, c| mask | 1 << c);
+            assert_eq!(*adjacent, connections, "{}", topology.id(point));
+        }
+
+        for mill in topology.mills() {
+            let mask = (1 << mill.first) | (1 << mill.second) | (1 << mill.third);
+            assert!(MILL_MASKS.contains(&mask));
+        }
+    }
+
+    #[test]
+    fn make_and_unmake() {
+        let mut board = BitBoard::empty_board();
+        let (one, two) = (PlayerId::One, PlayerId::Two);
+        let moves = [BitMove::Place { player_id: one, point: 0 }, BitMove::Place { player_id: one, point: 1 },
+                     BitMove::Place { player_id: two, point: 9 }, BitMove::Place { player_id: one, point: 2 }];
+        for mv in &moves {
+            board.make(*mv);
+        }
+        assert!(board.forms_mill(one, 2));
+        assert_eq!(board.removals(one, MillRemoval::FromMillsIfNoOther).len(), 1);
+
+        let remove = BitMove::Remove { player_id: one, point: 9 };
+        board.make(remove);
+        assert_eq!(board.count(two), 0);
+
+        board.unmake(remove);
+        for mv in moves.iter().rev() {
+            board.unmake(*mv);
+        }
+        assert_eq!(board, BitBoard::empty_board());
+    }
+}
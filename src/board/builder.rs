@@ -1,64 +1,161 @@
-use std::collections::HashMap;
-use std::collections::HashSet;
-
 use board::Board;
-use board::Position;
-use board::Direction;
-use board::Direction::*;
-
-pub fn build() -> Board {
-    let board = Board {
-        positions: Vec::new(),
-        ids_to_positions: HashMap::new(),
-        p1_mills: HashSet::new(),
-        p2_mills: HashSet::new(),
-    };
-
-    generate_positions(board)
+use board::BoardKind;
+use board::BoardTopology;
+
+// Sides of a square, clockwise from the top left corner
+const SIDES: [&str; 8] = ["nw", "n", "ne", "e", "se", "s", "sw", "w"];
+const CORNERS: [&str; 4] = ["nw", "ne", "se", "sw"];
+const MIDDLES: [&str; 4] = ["n", "e", "s", "w"];
+
+pub fn build(kind: BoardKind) -> Board {
+    Board::new(generate_topology(kind))
 }
 
-pub fn generate_positions(mut board: Board) -> Board {
+pub fn generate_topology(kind: BoardKind) -> BoardTopology {
+    let mut topology = BoardTopology::new();
 
-    let (mut prev_north, mut prev_south, mut prev_east, mut prev_west) = (None, None, None, None);
-    for layer in 0..3 {
+    generate_squares(&mut topology, kind.layers());
+
+    if kind.has_cross_section_mills() {
+        add_cross_section_mills(&mut topology);
+    }
+
+    if kind.has_diagonals() {
+        add_diagonals(&mut topology);
+    }
+
+    if kind.has_centre() {
+        add_centre(&mut topology);
+    }
 
-        let nw = board.new_blank_position(format!("{}nw", layer));
-        let ne = board.new_blank_position(format!("{}ne", layer));
-        let sw = board.new_blank_position(format!("{}sw", layer));
-        let se = board.new_blank_position(format!("{}se", layer));
+    topology
+}
+
+// Nested squares, each joined to the next through the middle of its sides
+fn generate_squares(topology: &mut BoardTopology, layers: i8) {
+    let far_edge = 2 * layers;
+    let middle = layers;
+
+    for layer in 0..layers {
+        let (near, far) = (layer, far_edge - layer);
+        let coordinates = [(near, near), (middle, near), (far, near), (far, middle),
+                           (far, far), (middle, far), (near, far), (near, middle)];
+
+        let square: Vec<usize> = SIDES.iter().zip(coordinates.iter())
+            .map(|(side, &(x, y))| topology.add_point(&id(layer, side), x, y))
+            .collect();
+
+        for (i, point) in square.iter().enumerate() {
+            topology.connect(*point, square[(i + 1) % square.len()]);
+        }
+
+        for i in 0..4 {
+            topology.add_mill(square[2 * i], square[2 * i + 1], square[(2 * i + 2) % square.len()]);
+        }
+
+        if layer > 0 {
+            for middle in &MIDDLES {
+                let outer = index(topology, layer - 1, middle);
+                let inner = index(topology, layer, middle);
+                topology.connect(outer, inner);
+            }
+        }
+    }
+}
 
-        let north = board.add_position(Position::new(format!("{}n", layer), prev_north, Some(ne), None, Some(nw)));
-        let east  = board.add_position(Position::new(format!("{}e", layer), Some(ne), prev_east, Some(se), None));
-        let south = board.add_position(Position::new(format!("{}s", layer), None, Some(se), prev_south, Some(sw)));
-        let west  = board.add_position(Position::new(format!("{}w", layer), Some(nw), None, Some(sw), prev_west));
+fn add_cross_section_mills(topology: &mut BoardTopology) {
+    for middle in &MIDDLES {
+        let (outer, centre, inner) = (index(topology, 0, middle), index(topology, 1, middle), index(topology, 2, middle));
+        topology.add_mill(outer, centre, inner);
+    }
+}
 
-        board.positions[nw].add_neighbour(East,  Some(north));
-        board.positions[nw].add_neighbour(South, Some(west));
-        board.positions[ne].add_neighbour(West,  Some(north));
-        board.positions[ne].add_neighbour(South, Some(east));
-        board.positions[sw].add_neighbour(North, Some(west));
-        board.positions[sw].add_neighbour(East,  Some(south));
-        board.positions[se].add_neighbour(North, Some(east));
-        board.positions[se].add_neighbour(West,  Some(south));
+// Join the corners of each square to the same corner of the next square in
+fn add_diagonals(topology: &mut BoardTopology) {
+    for corner in &CORNERS {
+        let (outer, centre, inner) = (index(topology, 0, corner), index(topology, 1, corner), index(topology, 2, corner));
+        topology.connect(outer, centre);
+        topology.connect(centre, inner);
+        topology.add_mill(outer, centre, inner);
+    }
+}
 
-        add_connection_to_prev(&mut board, South, prev_north, north);
-        add_connection_to_prev(&mut board, West,  prev_east, east);
-        add_connection_to_prev(&mut board, North, prev_south, south);
-        add_connection_to_prev(&mut board, East,  prev_west, west);
+// Centre of the Three Men's Morris board, connected to every point on the (only) square
+fn add_centre(topology: &mut BoardTopology) {
+    let centre = topology.add_point("c", 1, 1);
 
+    for side in &SIDES {
+        let point = index(topology, 0, side);
+        topology.connect(point, centre);
+    }
 
-        prev_north = Some(north);
-        prev_east  = Some(east);
-        prev_south = Some(south);
-        prev_west  = Some(west);
+    for &(first, third) in &[("n", "s"), ("e", "w"), ("nw", "se"), ("ne", "sw")] {
+        let (first, third) = (index(topology, 0, first), index(topology, 0, third));
+        topology.add_mill(first, centre, third);
     }
+}
+
+fn id(layer: i8, side: &str) -> String {
+    format!("{}{}", layer, side)
+}
 
-    return board;
+fn index(topology: &BoardTopology, layer: i8, side: &str) -> usize {
+    topology.index_of(&id(layer, side)).unwrap()
 }
 
-fn add_connection_to_prev(board: &mut Board, direction: Direction, from: Option<usize>, to: usize) {
-    if let Some(p) = from {
-        let f = board.positions.get_mut(p).unwrap();
-        f.add_neighbour(direction, Some(to));
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::Ply;
+    use player::PlayerId::One;
+
+    fn edges(topology: &BoardTopology) -> usize {
+        (0..topology.len()).map(|p| topology.connections(p).len()).sum::<usize>() / 2
+    }
+
+    #[test]
+    fn generates_each_kind_of_board() {
+        // (kind, points, connections, mills)
+        let expected = [
+            (BoardKind::NineMen, 24, 32, 16),
+            (BoardKind::TwelveMen, 24, 40, 20),
+            (BoardKind::SixMen, 16, 20, 8),
+            (BoardKind::ThreeMen, 9, 16, 8),
+        ];
+
+        for &(kind, points, connections, mills) in &expected {
+            let topology = generate_topology(kind);
+            assert_eq!(topology.len(), points, "{:?}", kind);
+            assert_eq!(edges(&topology), connections, "{:?}", kind);
+            assert_eq!(topology.mills().len(), mills, "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn detects_mills_on_each_board() {
+        // (kind, a line that's a mill, a line that isn't)
+        let lines = [
+            (BoardKind::NineMen, ["0n", "1n", "2n"], ["0nw", "1nw", "2nw"]),
+            (BoardKind::TwelveMen, ["0nw", "1nw", "2nw"], ["0nw", "0n", "1n"]),
+            (BoardKind::SixMen, ["1sw", "1w", "1nw"], ["0w", "1w", "1nw"]),
+            (BoardKind::ThreeMen, ["0nw", "c", "0se"], ["0nw", "0n", "c"]),
+        ];
+
+        for &(kind, mill, not_mill) in &lines {
+            for &(line, is_mill) in &[(mill, true), (not_mill, false)] {
+                let mut board = build(kind);
+                let points: Vec<usize> = line.iter().map(|id| board.topology().index_of(id).unwrap()).collect();
+                for &point in &points {
+                    board.place_piece(Ply::Placement { player_id: One, piece_id: point });
+                }
+                assert_eq!(board.forms_mill(One, points[2]), is_mill, "{:?} {:?}", kind, line);
+            }
+        }
+
+        // Six Men's Morris only has two squares, so nothing goes through the middle of the sides
+        let topology = generate_topology(BoardKind::SixMen);
+        for id in &["0n", "0e", "0s", "0w", "1n", "1e", "1s", "1w"] {
+            assert_eq!(topology.mills_through(topology.index_of(id).unwrap()).len(), 1, "{}", id);
+        }
     }
 }
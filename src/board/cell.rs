@@ -0,0 +1,52 @@
+// What's at a single point on the board. How points connect is in the BoardTopology
+
+use std::fmt;
+
+use player::PlayerId;
+
+use self::Cell::*;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Cell {
+    #[default]
+    Empty,
+    Piece(PlayerId),
+}
+
+impl Cell {
+    pub fn place(&mut self, player_id: PlayerId) {
+        match *self {
+            Empty => *self = Piece(player_id),
+            Piece(owner) => panic!("Position already has piece belonging to Player {}", owner),
+        }
+    }
+
+    pub fn remove(&mut self) {
+        *self = Empty;
+    }
+
+    pub fn owner(&self) -> Option<PlayerId> {
+        match *self {
+            Empty => None,
+            Piece(owner) => Some(owner),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Empty
+    }
+
+    pub fn owned_by(&self, player_id: PlayerId) -> bool {
+        *self == Piece(player_id)
+    }
+}
+
+// 0 for empty, otherwise the player's number
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Empty => write!(f, "0"),
+            Piece(owner) => write!(f, "{}", owner),
+        }
+    }
+}
@@ -0,0 +1,38 @@
+// The shape of board being played on
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum BoardKind {
+    NineMen,
+    // Nine Men's Morris board plus diagonals joining the corners of the squares
+    TwelveMen,
+    // Two squares, without mills through the middle of the board
+    SixMen,
+    // A 3x3 grid: one square plus a centre point joined to everything
+    ThreeMen,
+}
+
+use self::BoardKind::*;
+
+impl BoardKind {
+    // Number of nested squares
+    pub fn layers(&self) -> i8 {
+        match *self {
+            NineMen | TwelveMen => 3,
+            SixMen => 2,
+            ThreeMen => 1,
+        }
+    }
+
+    pub fn has_diagonals(&self) -> bool {
+        *self == TwelveMen
+    }
+
+    // Only with three squares are there three positions in a line through the middle of each side
+    pub fn has_cross_section_mills(&self) -> bool {
+        self.layers() == 3
+    }
+
+    pub fn has_centre(&self) -> bool {
+        *self == ThreeMen
+    }
+}
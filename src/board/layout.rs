@@ -0,0 +1,125 @@
+// Plain text description of a board, one statement per line:
+//
+//   # comments and blank lines are ignored
+//   point <id> <x> <y>          a point at grid coordinates (x, y), (0, 0) is top left
+//   connect <id> <id>           pieces can move between these points
+//   mill <id> <id> <id>         three points that make a mill
+//
+// Points must be declared before they're used in a connect or mill.
+
+use std::error::Error;
+use std::fmt;
+
+use board::BoardTopology;
+use zobrist::MAX_POINTS;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LayoutError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid layout on line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for LayoutError {}
+
+pub fn parse_layout(layout: &str) -> Result<BoardTopology, LayoutError> {
+    let mut topology = BoardTopology::new();
+
+    for (i, line) in layout.lines().enumerate() {
+        let line_number = i + 1;
+        let error = |message: String| LayoutError { line: line_number, message };
+
+        let words: Vec<&str> = line.split('#').next().unwrap().split_whitespace().collect();
+        if words.is_empty() {
+            continue;
+        }
+
+        let point = |id: &str| topology.index_of(id)
+            .ok_or_else(|| error(format!("unknown point {}", id)));
+
+        match (words[0], words.len()) {
+            ("point", 4) => {
+                let id = words[1];
+                if topology.index_of(id).is_some() {
+                    return Err(error(format!("point {} declared twice", id)));
+                }
+                let coordinate = |c: &str| c.parse::<i8>().ok().filter(|c| *c >= 0)
+                    .ok_or_else(|| error(format!("invalid coordinate {}", c)));
+                if topology.len() == MAX_POINTS {
+                    return Err(error(format!("boards can have at most {} points", MAX_POINTS)));
+                }
+                let (x, y) = (coordinate(words[2])?, coordinate(words[3])?);
+                topology.add_point(id, x, y);
+            },
+            ("connect", 3) => {
+                let (first, second) = (point(words[1])?, point(words[2])?);
+                if first == second {
+                    return Err(error(format!("can't connect {} to itself", words[1])));
+                }
+                topology.connect(first, second);
+            },
+            ("mill", 4) => {
+                let (first, second, third) = (point(words[1])?, point(words[2])?, point(words[3])?);
+                if first == second || second == third || first == third {
+                    return Err(error("mill must be three different points".to_string()));
+                }
+                topology.add_mill(first, second, third);
+            },
+            ("point", _) | ("connect", _) | ("mill", _) =>
+                return Err(error(format!("wrong number of arguments for {}", words[0]))),
+            (keyword, _) => return Err(error(format!("unknown statement {}", keyword))),
+        }
+    }
+
+    if topology.is_empty() {
+        return Err(LayoutError { line: 0, message: "layout has no points".to_string() });
+    }
+
+    Ok(topology)
+}
+
+pub fn to_layout_str(topology: &BoardTopology) -> String {
+    let mut layout = String::new();
+
+    for point in topology.points() {
+        layout += &format!("point {} {} {}\n", point.id, point.x, point.y);
+    }
+
+    for from in 0..topology.len() {
+        for to in topology.connections(from).iter().filter(|to| **to > from) {
+            layout += &format!("connect {} {}\n", topology.id(from), topology.id(*to));
+        }
+    }
+
+    for mill in topology.mills() {
+        layout += &format!("mill {} {} {}\n",
+            topology.id(mill.first), topology.id(mill.second), topology.id(mill.third));
+    }
+
+    layout
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use board::{generate_topology, BoardKind};
+
+    #[test]
+    fn round_trips_built_in_boards() {
+        for kind in &[BoardKind::NineMen, BoardKind::TwelveMen, BoardKind::SixMen, BoardKind::ThreeMen] {
+            let topology = generate_topology(*kind);
+            assert_eq!(parse_layout(&to_layout_str(&topology)), Ok(topology));
+        }
+    }
+
+    #[test]
+    fn reports_line_of_error() {
+        let layout = "# a line\npoint a 0 0\npoint b 1 0\n\nmill a b c\n";
+        assert_eq!(parse_layout(layout).unwrap_err().line, 5);
+    }
+}
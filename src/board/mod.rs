@@ -1,112 +1,116 @@
 mod builder;
-mod position;
-mod direction;
+mod cell;
 mod mill;
+mod kind;
+mod topology;
+mod layout;
+mod algebraic;
+mod bitboard;
 
 use std::collections::HashMap;
-use std::collections::HashSet;
 use std::fmt;
+use std::sync::Arc;
 
 use term_painter::Color::*;
 use term_painter::Painted;
 use term_painter::ToStyle;
 
-use self::position::Position;
-use self::direction::Direction;
-use self::mill::Mill;
-pub use self::builder::build;
+pub use self::cell::Cell;
+pub use self::mill::Mill;
+pub use self::builder::{build, generate_topology};
+pub use self::kind::BoardKind;
+pub use self::topology::{BoardTopology, Point};
+pub use self::layout::LayoutError;
+pub use self::bitboard::{BitBoard, BitMove, MoveList};
 
-use game::switch_player_id;
+use error::NinemanError;
 use game::Ply;
+use game::MillRemoval;
+use player::PlayerId;
+use zobrist;
 
+// Characters between points when printing
+const X_SPACING: i32 = 4;
+const Y_SPACING: i32 = 2;
 
 // Idea for a list of indices borrowed from here: https://rust-leipzig.github.io/architecture/2016/12/20/idiomatic-trees-in-rust/
+// The topology never changes during a game, so is shared rather than copied with every Board
 
 #[derive(Clone, Eq, PartialEq)]
 pub struct Board {
-    pub positions: Vec<Position>,
-    pub ids_to_positions: HashMap<String, usize>,
-    p1_mills: HashSet<Mill>,
-    p2_mills: HashSet<Mill>,
+    topology: Arc<BoardTopology>,
+    cells: Vec<Cell>,
+    // Zobrist hash of the pieces, kept up to date by place and remove
+    hash: u64,
 }
 
 impl Board {
-    // move these over to builder at some point
-    fn add_position(&mut self, position: Position) -> usize {
-        let next_index = self.positions.len();
+    pub fn new(topology: BoardTopology) -> Board {
+        assert!(topology.len() <= zobrist::MAX_POINTS, "Boards can have at most {} points", zobrist::MAX_POINTS);
 
-        self.ids_to_positions.insert(position.id.to_owned(), next_index);
+        Board {
+            cells: vec![Cell::Empty; topology.len()],
+            topology: Arc::new(topology),
+            hash: 0,
+        }
+    }
 
-        self.positions.push(position);
+    // See layout.rs for the format
+    pub fn from_layout_str(layout: &str) -> Result<Board, LayoutError> {
+        layout::parse_layout(layout).map(Board::new)
+    }
 
-        return next_index;
+    pub fn to_layout_string(&self) -> String {
+        layout::to_layout_str(&self.topology)
     }
 
-    fn new_blank_position(&mut self, id: String) -> usize {
-        return self.add_position(Position::blank(id));
+    pub fn topology(&self) -> &BoardTopology {
+        &self.topology
     }
 
-    pub fn available_places(&self, player_id: i8) -> Vec<Ply> {
-        self.positions.iter()
-            .filter(|p| p.is_empty())
-            .map(|p| Ply::Placement{ player_id: player_id, piece_id: p.id.to_owned() })
+    pub fn available_places(&self, player_id: PlayerId) -> Vec<Ply> {
+        self.points_where(|p| p.is_empty())
+            .map(|p| Ply::Placement{ player_id, piece_id: p })
             .collect()
     }
 
-    pub fn available_mills(&self, current_player_id: i8, opponent_id: i8) -> Vec<Ply> {
-        assert_eq!(current_player_id, switch_player_id(opponent_id));
+    pub fn available_mills(&self, current_player_id: PlayerId, removal: MillRemoval) -> Vec<Ply> {
+        let opponent_id = current_player_id.other();
 
-        let opponent_position_ids: HashSet<String>
-            = self.positions.iter()
-                .filter(|p| p.owned_by(opponent_id))
-                .map(|p| p.id.to_owned())
-                .collect();
+        let opponent_points: Vec<usize> = self.points_where(|p| p.owned_by(opponent_id)).collect();
 
-        let mills;
-        match opponent_id {
-            1 => mills = &self.p1_mills,
-            2 => mills = &self.p2_mills,
-            _ => panic!("Unknown player {}", opponent_id),
-        }
-
-        let mut in_mills = HashSet::new();
-        for mill in mills {
-            in_mills.insert(self.get_id(Some(mill.first)));
-            in_mills.insert(self.get_id(Some(mill.second)));
-            in_mills.insert(self.get_id(Some(mill.third)));
-        }
-
-        let not_in_mills: Vec<String>
-            = opponent_position_ids
-                .difference(&in_mills)
-                .map(|p| p.to_owned())
+        let not_in_mills: Vec<usize>
+            = opponent_points.iter()
+                .filter(|p| !self.in_mill(**p))
+                .cloned()
                 .collect();
 
-        // if not_in_mills.len() == 0 {
-        //     opponent_position_ids.into_iter().collect()
-        // } else {
-        //     not_in_mills
-        // }
+        let removable = match removal {
+            MillRemoval::FromMillsIfNoOther if not_in_mills.is_empty() => opponent_points,
+            MillRemoval::FromMillsIfNoOther | MillRemoval::NotFromMills => not_in_mills,
+            MillRemoval::AnyPiece => opponent_points,
+        };
 
-        not_in_mills.into_iter()
-            .map(|id| Ply::Mill { player_id: current_player_id, piece_id: id })
+        removable.into_iter()
+            .map(|p| Ply::Mill { player_id: current_player_id, piece_id: p })
             .collect()
     }
 
-    pub fn available_moves(&self, player_id: i8) -> Vec<Ply> {
-        let owned: Vec<&Position>
-            = self.positions.iter()
-                .filter(|p| p.owned_by(player_id))
-                .collect();
-
+    // When flying, a piece can move to any empty point rather than just connected ones
+    pub fn available_moves(&self, player_id: PlayerId, flying: bool) -> Vec<Ply> {
         let mut available_moves = Vec::new();
-        for position in owned {
-            for c in position.connections() {
-                let connection = self.positions.get(*c).unwrap();
-                if connection.is_empty() {
-                    let mv = (position.id.to_owned(), connection.id.to_owned());
-                    available_moves.push(Ply::Move { player_id: player_id, mv: mv });
-                }
+        for from in self.points_where(|p| p.owned_by(player_id)) {
+            let destinations: Vec<usize> = if flying {
+                self.points_where(|p| p.is_empty()).collect()
+            } else {
+                self.topology.connections(from).iter()
+                    .filter(|c| self.cells[**c].is_empty())
+                    .cloned()
+                    .collect()
+            };
+
+            for to in destinations {
+                available_moves.push(Ply::Move { player_id, mv: (from, to) });
             }
         }
 
@@ -114,275 +118,237 @@ impl Board {
     }
 
     pub fn place_piece(&mut self, placement_ply: Ply) {
-        let position = self.get_mut_position(placement_ply.piece_id());
-        position.place(placement_ply.player_id());
+        self.try_place_piece(placement_ply).unwrap_or_else(|e| panic!("{}", e));
     }
 
-    pub fn move_piece(&mut self, move_ply: Ply) {
-        let player_id = move_ply.player_id();
-        let (from_id, to_id) = move_ply.mv();
-        let can_move;
-        {
-            let from = self.get_position(&from_id);
-            let to   = self.get_position(&to_id);
-            can_move = from.owned_by(player_id) && to.is_empty() && self.are_connected(from,to);
+    // The try_ versions check everything before changing anything, so the board is left as it
+    // was when they return an Err
+    pub fn try_place_piece(&mut self, placement_ply: Ply) -> Result<(), NinemanError> {
+        let player_id = placement_ply.player_id();
+        let point = self.check_point(placement_ply.try_piece_id()?)?;
+
+        if !self.cells[point].is_empty() {
+            return Err(NinemanError::PointOccupied(point));
         }
 
+        self.place(point, player_id);
+        Ok(())
+    }
+
+    pub fn move_piece(&mut self, move_ply: Ply, flying: bool) {
+        self.try_move_piece(move_ply, flying).unwrap_or_else(|e| panic!("{}", e));
+    }
+
+    pub fn try_move_piece(&mut self, move_ply: Ply, flying: bool) -> Result<(), NinemanError> {
+        let player_id = move_ply.player_id();
+        let (from, to) = move_ply.try_mv()?;
+        let (from, to) = (self.check_point(from)?, self.check_point(to)?);
+
+        let can_move = self.cells[from].owned_by(player_id) && self.cells[to].is_empty()
+            && (flying || self.topology.are_connected(from, to));
+
         if can_move {
-            self.get_mut_position(from_id).remove();
-            self.get_mut_position(to_id).place(player_id);
+            self.remove(from);
+            self.place(to, player_id);
+            Ok(())
         } else {
-            panic!("Invalid move from {}: ({},{})", player_id, from_id, to_id);
+            Err(NinemanError::InvalidMove { player_id, from, to })
         }
     }
 
-    pub fn perform_mill(&mut self, mill_ply: Ply) {
-        let id = mill_ply.piece_id();
-        let player_id = mill_ply.player_id();
-        let opponent_id = switch_player_id(player_id);
+    pub fn perform_mill(&mut self, mill_ply: Ply, removal: MillRemoval) {
+        self.try_perform_mill(mill_ply, removal).unwrap_or_else(|e| panic!("{}", e));
+    }
 
-        let available_mills = self.available_mills(player_id, opponent_id);
-        let available_mills_other = self.available_mills(opponent_id, player_id);
+    // The piece has to be the opponent's, and one removal allows taking
+    pub fn try_perform_mill(&mut self, mill_ply: Ply, removal: MillRemoval) -> Result<(), NinemanError> {
+        let player_id = mill_ply.player_id();
+        let point = self.check_point(mill_ply.try_piece_id()?)?;
 
-        {
-            let position = self.get_mut_position(id);
-            if !position.is_empty() && !position.owned_by(player_id) {
-                position.remove();
-            } else {
-                panic!("Invalid mill by player {}: {}; available_mills: {:?}, available_mills_other: {:?}",
-                            player_id, position.id, available_mills, available_mills_other);
-            }
+        if self.available_mills(player_id, removal).contains(&mill_ply) {
+            self.remove(point);
+            Ok(())
+        } else {
+            Err(NinemanError::InvalidRemoval { player_id, point })
         }
-
-        self.validate_mills(player_id);
     }
 
-    fn validate_mills(&mut self, from: i8) {
-        let mut mills;
-        match from {
-            1 => mills = self.p1_mills.clone(),
-            2 => mills = self.p2_mills.clone(),
-            _ => panic!("Player {} found!", from),
-        }
-        mills.retain(|&m| self.is_mill(2, &m));
-        match from {
-            1 => self.p2_mills = mills,
-            2 => self.p1_mills = mills,
-            _ => panic!("Player {} found!", from),
+    fn check_point(&self, point: usize) -> Result<usize, NinemanError> {
+        if point < self.cells.len() {
+            Ok(point)
+        } else {
+            Err(NinemanError::InvalidPoint(point))
         }
     }
 
-    // TODO: consider MillFinder struct or something - or is that too Java-y?
-    pub fn update_mills(&mut self, player_id: i8) -> bool {
-        let mills = self.find_mills(player_id);
-
-        let new_mills: HashSet<Mill>;
-        match player_id {
-            1 => {
-                new_mills = mills.difference(&self.p1_mills).map(|t| *t).collect();
-                //println!("found mills: {}", self.mills_string(&mills));
-                //println!("p1_mills (old): {}", self.mills_string(&self.p1_mills));
-                self.p1_mills = mills;
-            },
-            2 => {
-                new_mills = mills.difference(&self.p2_mills).map(|t| *t).collect();
-                //println!("found mills: {}", self.mills_string(&mills));
-                //println!("p2_mills (old): {}", self.mills_string(&self.p2_mills));
-                self.p2_mills = mills;
-            },
-            _ => {
-                panic!("Unknown player_id: {}", player_id);
-            }
-        }
+    // Reverse place_piece, move_piece and perform_mill. Only used by GameState::undo, so the plies
+    // are trusted to have been made
+    pub fn undo_placement(&mut self, placement_ply: &Ply) {
+        self.remove(placement_ply.piece_id());
+    }
 
+    pub fn undo_move(&mut self, move_ply: &Ply) {
+        let (from, to) = move_ply.mv();
+        self.remove(to);
+        self.place(from, move_ply.player_id());
+    }
 
-        self.can_mill(player_id, new_mills)
+    pub fn undo_mill(&mut self, mill_ply: &Ply) {
+        self.place(mill_ply.piece_id(), mill_ply.player_id().other());
+    }
 
+    // All changes to pieces go through place and remove, to keep the hash up to date
+    fn place(&mut self, point: usize, player_id: PlayerId) {
+        self.cells[point].place(player_id);
+        self.hash ^= zobrist::piece(point, player_id);
     }
 
-    pub fn can_mill(&self, player_id: i8, new_mills: HashSet<Mill>) -> bool {
-        match new_mills.len() {
-            0 => false,
-            1 => true,
-            2 => true,
-            _ => {
-                self.print();
-                // FIXME: should be panicking here as we shouldn't be able to create 3 mills in a turn!!
-                println!("DANGER: Have somehow created {} mills this turn for player {}: {}",
-                    new_mills.len(), player_id, self.mills_string(&new_mills));
-                true
-            },
+    fn remove(&mut self, point: usize) {
+        if let Some(player_id) = self.cells[point].owner() {
+            self.hash ^= zobrist::piece(point, player_id);
         }
+        self.cells[point].remove();
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.hash
     }
 
-    fn mills_string(&self, mills: &HashSet<Mill>) -> String {
-        let mut mills_string = String::new();
+    // A mill is made when the piece just placed or moved to point completes a line of three
+    pub fn forms_mill(&self, player_id: PlayerId, point: usize) -> bool {
+        self.topology.mills_through(point).iter().any(|m| self.is_mill(player_id, m))
+    }
 
-        for mill in mills {
-            mills_string.push_str(
-                &format!("({},{},{})",
-                    self.positions.get(mill.first).unwrap().id,
-                    self.positions.get(mill.second).unwrap().id,
-                    self.positions.get(mill.third).unwrap().id,
-                )
-            );
+    // Whether the piece at point is part of a mill belonging to its owner
+    pub fn in_mill(&self, point: usize) -> bool {
+        match self.cells[point].owner() {
+            Some(owner) => self.topology.mills_through(point).iter().any(|m| self.is_mill(owner, m)),
+            None => false,
         }
+    }
 
-        mills_string
+    fn is_mill(&self, player_id: PlayerId, mill: &Mill) -> bool {
+        self.cells[mill.first].owned_by(player_id) &&
+        self.cells[mill.second].owned_by(player_id) &&
+        self.cells[mill.third].owned_by(player_id)
     }
 
-    fn find_mills(&self, player_id: i8) -> HashSet<Mill> {
-        let mut mills = HashSet::new();
-        for layer in 0..3 {
-            for side in Direction::iterator() {
-                if let Some(mill) = self.find_mill_for_side(player_id, layer, side) {
-                    //println!("Mill found for {}: {}", player_id, self.mill_str(&mill));
-                    mills.insert(mill);
-                }
-            }
-        }
+    pub fn is_full(&self) -> bool {
+        self.cells.iter().all(|c| !c.is_empty())
+    }
 
-        for cross_section in Direction::iterator() {
-            if let Some(mill) = self.find_mill_for_cross_section(player_id, cross_section) {
-                //println!("Mill found for {}: {}", player_id, self.mill_str(&mill));
-                mills.insert(mill);
-            }
-        }
+    fn points_where<'a, F>(&'a self, predicate: F) -> impl Iterator<Item = usize> + 'a
+        where F: Fn(&Cell) -> bool + 'a {
+        self.cells.iter().enumerate()
+            .filter(move |&(_, p)| predicate(p))
+            .map(|(i, _)| i)
+    }
 
-        mills
+    pub fn get_id(&self, point: usize) -> String {
+        self.topology.id(point).to_owned()
     }
 
-    fn find_mill_for_side(&self, player_id: i8, layer: i8, side: &Direction) -> Option<Mill> {
-        match side {
-            &Direction::North => self.mill(player_id, &format!("{}ne", layer), &format!("{}n", layer), &format!("{}nw", layer)),
-            &Direction::East  => self.mill(player_id, &format!("{}ne", layer), &format!("{}e", layer), &format!("{}se", layer)),
-            &Direction::South => self.mill(player_id, &format!("{}se", layer), &format!("{}s", layer), &format!("{}sw", layer)),
-            &Direction::West  => self.mill(player_id, &format!("{}nw", layer), &format!("{}w", layer), &format!("{}sw", layer)),
-        }
+    // Algebraic notation, e.g. a7 for 0nw. See algebraic.rs
+    pub fn square(&self, point: usize) -> String {
+        algebraic::square(&self.topology, point)
     }
 
-    fn find_mill_for_cross_section(&self, player_id: i8, cross_section: &Direction) -> Option<Mill> {
-        match cross_section {
-            &Direction::North => self.mill(player_id, "0n", "1n", "2n"),
-            &Direction::East  => self.mill(player_id, "0e", "1e", "2e"),
-            &Direction::South => self.mill(player_id, "0s", "1s", "2s"),
-            &Direction::West  => self.mill(player_id, "0w", "1w", "2w"),
-        }
+    pub fn point_at_square(&self, square: &str) -> Option<usize> {
+        algebraic::parse_square(&self.topology, square)
     }
 
-    fn mill(&self, player_id: i8, first: &str, second: &str, third: &str) -> Option<Mill> {
-        let mill = Mill {
-            first: self.ids_to_positions[first],
-            second: self.ids_to_positions[second],
-            third: self.ids_to_positions[third]};
-        match self.is_mill(player_id, &mill) {
-            true =>  Some(mill),
-            false => None,
-        }
+    pub fn id_to_square(&self, id: &str) -> Option<String> {
+        self.topology.index_of(id).map(|p| self.square(p))
     }
 
-    fn is_mill(&self, player_id: i8, mill: &Mill) -> bool {
-        self.positions.get(mill.first).unwrap().owned_by(player_id) &&
-        self.positions.get(mill.second).unwrap().owned_by(player_id) &&
-        self.positions.get(mill.third).unwrap().owned_by(player_id)
+    pub fn square_to_id(&self, square: &str) -> Option<String> {
+        self.point_at_square(square).map(|p| self.get_id(p))
     }
 
-    fn mill_str(&self, mill: &Mill) -> String {
-        format!("({}, {}, {})",
-            self.positions.get(mill.first).unwrap().id,
-            self.positions.get(mill.second).unwrap().id,
-            self.positions.get(mill.third).unwrap().id)
+    pub fn piece_at(&self, point: usize) -> Cell {
+        self.cells[point]
     }
 
-    pub fn get_id(&self, position: Option<usize>) -> String {
-        match position {
-            Some(p) => self.positions[p].id.to_owned(),
-            None => "_".to_string(),
+    pub fn print(&self) {
+        let canvas = self.canvas();
+
+        let points: HashMap<(i32, i32), usize> = self.topology.points().iter().enumerate()
+            .map(|(i, p)| ((i32::from(p.x) * X_SPACING, i32::from(p.y) * Y_SPACING), i))
+            .collect();
+
+        // Painted writes its colours straight to the terminal, so can't be built up into a String
+        for (y, row) in canvas.iter().enumerate() {
+            for (x, c) in row.iter().enumerate() {
+                match points.get(&(x as i32, y as i32)) {
+                    Some(point) => print!("{}", self.styled_piece(*point)),
+                    None => print!("{}", c),
+                }
+            }
+            println!();
         }
     }
 
-    fn get_position_option(&self, id: &str) -> Option<&Position> {
-        match self.ids_to_positions.get(id) {
-            Some(index) => self.positions.get(*index),
-            None => None,
+    // Lines between connected points, drawn on a grid of characters. Points themselves are left blank
+    fn canvas(&self) -> Vec<Vec<char>> {
+        let width = (i32::from(self.topology.width()) - 1) * X_SPACING + 1;
+        let height = (i32::from(self.topology.height()) - 1) * Y_SPACING + 1;
+        let mut canvas = vec![vec![' '; width.max(0) as usize]; height.max(0) as usize];
+
+        let points = self.topology.points();
+        for (from, point) in points.iter().enumerate() {
+            for to in self.topology.connections(from).iter().filter(|to| **to > from) {
+                let other = &points[*to];
+                draw_line(&mut canvas,
+                          (i32::from(point.x) * X_SPACING, i32::from(point.y) * Y_SPACING),
+                          (i32::from(other.x) * X_SPACING, i32::from(other.y) * Y_SPACING));
+            }
         }
-    }
 
-    fn get_position(&self, id: &str) -> &Position {
-        self.get_position_option(id).unwrap()
+        canvas
     }
 
-    pub fn get_mut_position(&mut self, id: String) -> &mut Position {
-        let index = self.ids_to_positions.get(&id).unwrap().to_owned();
-        return &mut self.positions[index];
+    fn styled_piece(&self, point: usize) -> Painted<String> {
+        let cell = self.cells[point];
+        match cell {
+            Cell::Empty => White.paint(cell.to_string()),
+            Cell::Piece(PlayerId::One) => Green.paint(cell.to_string()),
+            Cell::Piece(PlayerId::Two) => Blue.paint(cell.to_string()),
+        }
     }
 
-    pub fn print(&self) {
-        println!("{}----------{}----------{}",
-            self.styled_piece("0nw"),
-            self.styled_piece("0n"),
-            self.styled_piece("0ne"));
-        println!("|          |          |");
-        println!("|   {}------{}------{}   |",
-            self.styled_piece("1nw"),
-            self.styled_piece("1n"),
-            self.styled_piece("1ne"));
-        println!("|   |      |      |   |");
-        println!("|   |   {}--{}--{}   |   |",
-            self.styled_piece("2nw"),
-            self.styled_piece("2n"),
-            self.styled_piece("2ne"));
-        println!("|   |   |     |   |   |");
-        println!("{}---{}---{}     {}---{}---{}",
-            self.styled_piece("0w"),
-            self.styled_piece("1w"),
-            self.styled_piece("2w"),
-            self.styled_piece("2e"),
-            self.styled_piece("1e"),
-            self.styled_piece("0e"));
-        println!("|   |   |     |   |   |");
-        println!("|   |   {}--{}--{}   |   |",
-            self.styled_piece("2sw"),
-            self.styled_piece("2s"),
-            self.styled_piece("2se"));
-        println!("|   |      |      |   |");
-        println!("|   {}------{}------{}   |",
-            self.styled_piece("1sw"),
-            self.styled_piece("1s"),
-            self.styled_piece("1se"));
-        println!("|          |          |");
-        println!("{}----------{}----------{}",
-            self.styled_piece("0sw"),
-            self.styled_piece("0s"),
-            self.styled_piece("0se"));
-    }
-
-    fn styled_piece(&self, pos: &str) -> Painted<String> {
-        let piece = self.get_position(pos).piece();
-        match piece {
-            0 => White.paint(piece.to_string()),
-            1 => Green.paint(piece.to_string()),
-            2 => Blue.paint(piece.to_string()),
-            _ => panic!("Unknown piece type {}", piece),
-        }
+    pub fn is_valid_position(&self, position: &str) -> bool {
+        self.topology.index_of(position).is_some()
     }
 
-    pub fn is_valid_position(&self, position: &String) -> bool {
-        match self.get_position_option(position) {
-            Some(_p) => true,
+    pub fn is_empty_position(&self, position: &str) -> bool {
+        match self.topology.index_of(position) {
+            Some(p) => self.cells[p].is_empty(),
             None => false
         }
     }
+}
 
-    pub fn is_empty_position(&self, position: &String) -> bool {
-        match self.get_position_option(position) {
-            Some(p) => p.is_empty(),
-            None => false
+fn draw_line(canvas: &mut [Vec<char>], (x1, y1): (i32, i32), (x2, y2): (i32, i32)) {
+    let mut draw = |x: i32, y: i32, c: char| {
+        let cell = &mut canvas[y as usize][x as usize];
+        if *cell == ' ' {
+            *cell = c;
         }
-    }
+    };
 
-    fn are_connected(&self, from: &Position, to: &Position) -> bool {
-        from.connected_to(self.ids_to_positions.get(&to.id))
+    if y1 == y2 {
+        for x in (x1.min(x2) + 1)..x1.max(x2) {
+            draw(x, y1, '-');
+        }
+    } else if x1 == x2 {
+        for y in (y1.min(y2) + 1)..y1.max(y2) {
+            draw(x1, y, '|');
+        }
+    } else {
+        let c = if (x2 - x1) * (y2 - y1) > 0 { '\\' } else { '/' };
+        for y in (y1.min(y2) + 1)..y1.max(y2) {
+            draw(x1 + (x2 - x1) * (y - y1) / (y2 - y1), y, c);
+        }
     }
 }
 
@@ -391,17 +357,16 @@ impl fmt::Debug for Board {
 
         let mut debug_string = String::new();
 
-        for position in &self.positions {
-            debug_string += &format!("({} -> {},{},{},{}; c: {}), ",
-                position.id,
-                self.get_id(position[Direction::North]),
-                self.get_id(position[Direction::East]),
-                self.get_id(position[Direction::South]),
-                self.get_id(position[Direction::West]),
-                position.connections_string());
+        for (i, point) in self.topology.points().iter().enumerate() {
+            let connections: Vec<&str> = self.topology.connections(i).iter()
+                .map(|c| self.topology.id(*c))
+                .collect();
+            debug_string += &format!("({} -> {:?}; p: {}), ",
+                point.id,
+                connections,
+                self.cells[i]);
         }
 
-        return write!(f, "{}", debug_string);
-
+        write!(f, "{}", debug_string)
     }
 }
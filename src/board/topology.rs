@@ -0,0 +1,105 @@
+// Description of a board's shape: its points, how they're connected, and which lines of three make mills.
+// Built once per board shape and shared between every Board (and so every GameState) using it.
+
+use std::collections::HashMap;
+
+use board::Mill;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Point {
+    pub id: String,
+    // Grid coordinates, with (0, 0) in the top left. Only used for rendering
+    pub x: i8,
+    pub y: i8,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BoardTopology {
+    points: Vec<Point>,
+    connections: Vec<Vec<usize>>,
+    mills: Vec<Mill>,
+    // For each point, indices into mills of every mill that point is part of
+    mills_through: Vec<Vec<usize>>,
+    ids_to_points: HashMap<String, usize>,
+}
+
+impl BoardTopology {
+    pub fn new() -> Self {
+        BoardTopology::default()
+    }
+
+    pub fn add_point(&mut self, id: &str, x: i8, y: i8) -> usize {
+        assert!(!self.ids_to_points.contains_key(id), "Point {} already exists", id);
+
+        let index = self.points.len();
+        self.points.push(Point { id: id.to_string(), x, y });
+        self.connections.push(Vec::new());
+        self.mills_through.push(Vec::new());
+        self.ids_to_points.insert(id.to_string(), index);
+
+        index
+    }
+
+    pub fn connect(&mut self, first: usize, second: usize) {
+        // Kept sorted so two topologies with the same connections are equal
+        if !self.are_connected(first, second) {
+            self.connections[first].push(second);
+            self.connections[first].sort();
+            self.connections[second].push(first);
+            self.connections[second].sort();
+        }
+    }
+
+    pub fn add_mill(&mut self, first: usize, second: usize, third: usize) {
+        let index = self.mills.len();
+        self.mills.push(Mill { first, second, third });
+        for point in &[first, second, third] {
+            self.mills_through[*point].push(index);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn points(&self) -> &Vec<Point> {
+        &self.points
+    }
+
+    pub fn id(&self, point: usize) -> &str {
+        &self.points[point].id
+    }
+
+    pub fn index_of(&self, id: &str) -> Option<usize> {
+        self.ids_to_points.get(id).cloned()
+    }
+
+    pub fn connections(&self, point: usize) -> &Vec<usize> {
+        &self.connections[point]
+    }
+
+    pub fn are_connected(&self, first: usize, second: usize) -> bool {
+        self.connections[first].contains(&second)
+    }
+
+    pub fn mills(&self) -> &Vec<Mill> {
+        &self.mills
+    }
+
+    pub fn mills_through(&self, point: usize) -> Vec<&Mill> {
+        self.mills_through[point].iter().map(|m| &self.mills[*m]).collect()
+    }
+
+    // Size of the grid the points sit on
+    pub fn width(&self) -> i8 {
+        self.points.iter().map(|p| p.x + 1).max().unwrap_or(0)
+    }
+
+    pub fn height(&self) -> i8 {
+        self.points.iter().map(|p| p.y + 1).max().unwrap_or(0)
+    }
+}
@@ -0,0 +1,43 @@
+// Errors from the try_ versions of functions that change the board or game state.
+// The versions without try_ panic with the same message, for callers that know their input is good
+
+use std::error::Error;
+use std::fmt;
+
+use game::Ply;
+use player::PlayerId;
+
+use self::NinemanError::*;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum NinemanError {
+    // Not the index of a point on the board
+    InvalidPoint(usize),
+    PointOccupied(usize),
+    // Moving from a point that isn't the player's, or to one that isn't connected or empty
+    InvalidMove{player_id: PlayerId, from: usize, to: usize},
+    // Removing a piece that isn't the opponent's, or that the RuleSet's MillRemoval protects
+    InvalidRemoval{player_id: PlayerId, point: usize},
+    // e.g. asking a Placement for its mv, or a Move during placement
+    WrongKindOfPly(Ply),
+    NotPlayersTurn(PlayerId),
+    NoPiecesToPlace,
+    NoPiecesToLose,
+}
+
+impl fmt::Display for NinemanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InvalidPoint(point) => write!(f, "No point {} on the board", point),
+            PointOccupied(point) => write!(f, "Point {} already has a piece", point),
+            InvalidMove{player_id, from, to} => write!(f, "Invalid move from {}: ({},{})", player_id, from, to),
+            InvalidRemoval{player_id, point} => write!(f, "Invalid mill by player {}: {}", player_id, point),
+            WrongKindOfPly(ply) => write!(f, "Can't do that with {:?}", ply),
+            NotPlayersTurn(player_id) => write!(f, "Not Player {}'s turn", player_id),
+            NoPiecesToPlace => write!(f, "No pieces left to place"),
+            NoPiecesToLose => write!(f, "No pieces on the board to lose"),
+        }
+    }
+}
+
+impl Error for NinemanError {}
@@ -0,0 +1,267 @@
+// One line description of a position, like chess's FEN. Six fields separated by spaces:
+//
+//   11....../22....../........ 1 7/7 - 0/0 0
+//
+//   points            what's on each point in board order, 1 or 2 for a player's piece and . for
+//                     empty, with a / after every 8 points (a square on the Nine Men's board)
+//   side to move      1 or 2
+//   pieces in hand    player 1's, then player 2's
+//   pending removal   x if the side to move has made a mill and has to remove a piece, otherwise -
+//   scores            player 1's, then player 2's
+//   moves since mill  for RuleSet::move_limit
+//
+// The rules aren't included, so have to be given to GameState::from_fen. Positions that can't come
+// up in a game played with those rules from the beginning are rejected.
+
+use std::error::Error;
+use std::fmt;
+
+use board;
+use board::{Board, Cell};
+use game::{GameState, Ply, RuleSet, WinCondition};
+use player::{PlayerId, PlayerState, MINIMUM_PIECES};
+
+const POINTS_PER_GROUP: usize = 8;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FenError {
+    pub message: String,
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid position: {}", self.message)
+    }
+}
+
+impl Error for FenError {}
+
+// Everything in a FEN, checked against the rules, for GameState::from_fen to put together
+pub struct Fen {
+    pub board: Board,
+    pub current_player_id: PlayerId,
+    pub player1_state: PlayerState,
+    pub player2_state: PlayerState,
+    pub pending_removal: bool,
+    pub moves_since_mill: u16,
+}
+
+pub fn to_fen(game_state: &GameState) -> String {
+    let board = &game_state.board;
+    let points: Vec<String> = (0..board.topology().len())
+        .map(|point| match board.piece_at(point) {
+            Cell::Empty => ".".to_string(),
+            Cell::Piece(player_id) => player_id.to_string(),
+        })
+        .collect();
+    let points: Vec<String> = points.chunks(POINTS_PER_GROUP).map(|group| group.concat()).collect();
+
+    let (player1, player2) = (&game_state.player1_state, &game_state.player2_state);
+    format!("{} {} {}/{} {} {}/{} {}",
+        points.join("/"),
+        game_state.current_player_id,
+        player1.pieces_left_to_place(), player2.pieces_left_to_place(),
+        if game_state.can_current_player_mill() { "x" } else { "-" },
+        player1.score(), player2.score(),
+        game_state.moves_since_mill())
+}
+
+pub fn parse_fen(fen: &str, rules: &RuleSet) -> Result<Fen, FenError> {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    if fields.len() != 6 {
+        return Err(error(format!("expected 6 fields, found {}", fields.len())));
+    }
+
+    let mut board = board::build(rules.board);
+    let points: Vec<char> = fields[0].split('/').flat_map(|group| group.chars()).collect();
+    let groups = fields[0].split('/').count();
+    if points.len() != board.topology().len() || groups != points.len().div_ceil(POINTS_PER_GROUP) {
+        return Err(error(format!("{} doesn't match a board of {} points", fields[0], board.topology().len())));
+    }
+    for (point, c) in points.iter().enumerate() {
+        let player_id = match *c {
+            '.' => continue,
+            '1' => PlayerId::One,
+            '2' => PlayerId::Two,
+            _ => return Err(error(format!("invalid point {}", c))),
+        };
+        board.place_piece(Ply::Placement { player_id, piece_id: point });
+    }
+
+    let current_player_id = match fields[1] {
+        "1" => PlayerId::One,
+        "2" => PlayerId::Two,
+        side => return Err(error(format!("invalid side to move {}", side))),
+    };
+    let hands = pair(fields[2], "pieces in hand")?;
+    let pending_removal = match fields[3] {
+        "x" => true,
+        "-" => false,
+        removal => return Err(error(format!("invalid pending removal {}", removal))),
+    };
+    let scores = pair(fields[4], "scores")?;
+    let moves_since_mill = fields[5].parse::<u16>().map_err(|_| error(format!("invalid moves since mill {}", fields[5])))?;
+
+    let on_board = |player_id: PlayerId| (0..board.topology().len())
+        .filter(|point| board.piece_at(*point).owned_by(player_id))
+        .count() as i8;
+
+    let states: Vec<PlayerState> = PlayerId::both().iter()
+        .map(|p| PlayerState::new(scores[p.index()], hands[p.index()], on_board(*p)))
+        .collect();
+
+    check(&board, rules, current_player_id, &states, pending_removal, moves_since_mill)?;
+
+    Ok(Fen {
+        board,
+        current_player_id,
+        player1_state: states[0].clone(),
+        player2_state: states[1].clone(),
+        pending_removal,
+        moves_since_mill,
+    })
+}
+
+// Turn down positions that can't be reached from the beginning
+fn check(board: &Board, rules: &RuleSet, current_player_id: PlayerId, states: &[PlayerState],
+         pending_removal: bool, moves_since_mill: u16) -> Result<(), FenError> {
+    let current = current_player_id.index();
+    let pieces = rules.pieces_per_player;
+
+    for (state, player_id) in states.iter().zip(PlayerId::both().iter()) {
+        if state.total_pieces() > pieces {
+            return Err(error(format!("player {} has more than {} pieces", player_id, pieces)));
+        }
+    }
+
+    // The game ends as soon as a player is down to one piece fewer than MINIMUM_PIECES, so nobody
+    // can have fewer than that, and only one player can be short
+    if rules.win_condition == WinCondition::TooFewPieces {
+        let short: Vec<i8> = states.iter().map(|s| s.total_pieces()).filter(|p| *p < MINIMUM_PIECES).collect();
+        if short.len() > 1 || short.iter().any(|p| *p < MINIMUM_PIECES - 1) {
+            return Err(error(format!("the game ends when a player has fewer than {} pieces", MINIMUM_PIECES)));
+        }
+    }
+
+    // Player 1 places first, so during placement has placed as many pieces as player 2, or one
+    // more if it's player 2's turn, or if player 1 is about to remove a piece
+    let placed: Vec<i8> = states.iter().map(|s| pieces - s.pieces_left_to_place()).collect();
+    let placement = states.iter().any(|s| s.is_placement());
+    let player1_ahead = (current_player_id == PlayerId::Two) != pending_removal;
+    if placement && placed[0] - placed[1] != if player1_ahead { 1 } else { 0 } {
+        return Err(error(format!("player 1 has placed {} pieces and player 2 {}", placed[0], placed[1])));
+    }
+    if placement && moves_since_mill != 0 {
+        return Err(error("moves made before placement is over".to_string()));
+    }
+
+    if pending_removal {
+        if rules.win_condition == WinCondition::Mill {
+            return Err(error("pieces aren't removed when a mill wins".to_string()));
+        }
+        let in_mill = (0..board.topology().len())
+            .any(|point| board.piece_at(point).owned_by(current_player_id) && board.in_mill(point));
+        if !in_mill {
+            return Err(error(format!("removal pending but player {} has no mill", current_player_id)));
+        }
+        if board.available_mills(current_player_id, rules.mill_removal).is_empty() {
+            return Err(error("removal pending but there's nothing to remove".to_string()));
+        }
+    }
+
+    // Each mill scores one, and removes one of the opponent's pieces straight after
+    for (i, state) in states.iter().enumerate() {
+        let lost = pieces - states[1 - i].total_pieces();
+        if rules.win_condition == WinCondition::Mill {
+            if lost != 0 {
+                return Err(error("pieces aren't removed when a mill wins".to_string()));
+            }
+            // The first mill wins, so only the winner can have a score, of one, and a mill to show for it
+            let player_id = PlayerId::both()[i];
+            let has_mill = (0..board.topology().len())
+                .any(|point| board.piece_at(point).owned_by(player_id) && board.in_mill(point));
+            if state.score() != if has_mill { 1 } else { 0 } {
+                return Err(error(format!("player {} has a score of {} with {} mill", i + 1, state.score(),
+                                         if has_mill { "a" } else { "no" })));
+            }
+            continue;
+        }
+
+        let expected = lost + if pending_removal && i == current { 1 } else { 0 };
+        if state.score() != expected {
+            return Err(error(format!("player {} has a score of {} but should have {}", i + 1, state.score(), expected)));
+        }
+    }
+
+    Ok(())
+}
+
+fn pair(field: &str, name: &str) -> Result<[i8; 2], FenError> {
+    let numbers: Vec<Option<i8>> = field.split('/').map(|n| n.parse().ok().filter(|n| *n >= 0)).collect();
+    match numbers[..] {
+        [Some(first), Some(second)] => Ok([first, second]),
+        _ => Err(error(format!("invalid {} {}", name, field))),
+    }
+}
+
+fn error(message: String) -> FenError {
+    FenError { message }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::Turn;
+    use game::game_state::last_turns;
+
+    #[test]
+    fn round_trips_every_position_in_a_game() {
+        let rules = RuleSet::standard().with_flying(true);
+        let mut game_state = GameState::at_beginning(rules);
+        let plies: Vec<Ply> = last_turns(rules, 60).iter().flat_map(Turn::plies).collect();
+
+        for ply in plies.iter().map(Some).chain(Some(None)) {
+            let fen = game_state.to_fen();
+            let parsed = GameState::from_fen(&fen, rules).unwrap();
+            assert_eq!(parsed.to_fen(), fen);
+            assert_eq!(parsed.hash(), game_state.hash());
+            assert_eq!(parsed.available_plies(), game_state.available_plies());
+
+            if let Some(ply) = ply {
+                game_state.apply(ply);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_impossible_positions() {
+        let rules = RuleSet::standard();
+        assert!(GameState::from_fen("1......./......../........ 2 8/9 - 0/0 0", rules).is_ok());
+
+        let invalid = [
+            "1......./......../....... 2 8/9 - 0/0 0",
+            "1......./......../........ 3 8/9 - 0/0 0",
+            "1......./......../........ 1 8/9 - 0/0 0",
+            "1......./......../........ 2 9/9 - 0/0 0",
+            "1......./......../........ 2 8/9 x 0/0 0",
+            "1......./......../........ 2 8/9 - 1/0 0",
+            "1......./......../........ 2 8/9 - 0/0 4",
+            "1......./......../........ 2 8/-9 - 0/0 0",
+            "111...../......../........ 2 0/0 - 0/0 0",
+            "1......./......../........ 2 0/0 - 9/8 0",
+        ];
+        for fen in &invalid {
+            assert!(GameState::from_fen(fen, rules).is_err(), "{}", fen);
+        }
+
+        // Two pieces each is one too few for both players
+        assert!(GameState::from_fen("11....../22....../........ 2 0/0 - 7/7 0", rules).is_err());
+        assert!(GameState::from_fen("11....../222...../........ 1 0/0 - 6/7 0", rules).is_ok());
+
+        let three_mens_morris = RuleSet::three_mens_morris();
+        assert!(GameState::from_fen("111.22../. 2 0/1 - 1/0 0", three_mens_morris).is_ok());
+        for fen in &["11..22../. 1 1/1 - 5/3 0", "11..22../. 1 1/1 - 1/0 0", "111.22../. 2 0/1 - 0/0 0"] {
+            assert!(GameState::from_fen(fen, three_mens_morris).is_err(), "{}", fen);
+        }
+    }
+}
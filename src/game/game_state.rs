@@ -1,136 +1,456 @@
 use std::fmt;
+use std::mem;
 
 use board;
 use board::Board;
 
-use player::PlayerState;
+use error::NinemanError;
+use player::{PlayerId, PlayerState};
 
+use game::{GameOutcome, DrawReason, IllegalMoveReason, WinReason};
+use game::fen;
+use game::FenError;
 use game::Ply;
 use game::Ply::*;
-use game::switch_player_id;
+use game::RuleSet;
+use game::Turn;
+use game::WinCondition;
+use zobrist;
 
-#[derive(Clone, Eq, PartialEq)]
 pub struct GameState {
     pub ply_to_get_here: Ply,
-    pub next_ply: Ply, // NB ignore specific move here, just ply type and player_id. Is this bad? maybe. Or maybe blank piece_ids just mean unknown
+    pub next_ply: Ply, // NB ignore specific move here, just ply type and player_id. Is this bad? maybe. piece_ids are always 0
     pub board: Board,
-    pub current_player_id: i8,
+    pub current_player_id: PlayerId,
     pub player1_state: PlayerState,
     pub player2_state: PlayerState,
+    pub rules: RuleSet,
+    // Moves since the last mill (placements don't count), for RuleSet::move_limit
+    moves_since_mill: u16,
+    undo_stack: Vec<Undo>,
+}
+
+// Everything apply changes that can't be worked out again from the ply itself
+#[derive(Clone, Debug)]
+struct Undo {
+    ply: Ply,
+    ply_to_get_here: Ply,
+    next_ply: Ply,
+    current_player_id: PlayerId,
+    player1_state: PlayerState,
+    player2_state: PlayerState,
+    moves_since_mill: u16,
 }
 
 impl GameState {
 
-    pub fn at_beginning() -> Self {
+    pub fn at_beginning(rules: RuleSet) -> Self {
+        GameState::with_board(board::build(rules.board), rules)
+    }
+
+    // For boards that aren't one of the built in kinds, e.g. from Board::from_layout_str.
+    // rules.board is ignored
+    pub fn with_board(board: Board, rules: RuleSet) -> Self {
         GameState {
-            board: board::build(),
+            board,
             ply_to_get_here: Root,
-            next_ply: Root,
-            current_player_id: 1,
-            player1_state: PlayerState::at_beginning(),
-            player2_state: PlayerState::at_beginning(),
+            next_ply: Placement {player_id: PlayerId::One, piece_id: 0},
+            current_player_id: PlayerId::One,
+            player1_state: PlayerState::at_beginning(&rules),
+            player2_state: PlayerState::at_beginning(&rules),
+            rules,
+            moves_since_mill: 0,
+            undo_stack: Vec::new(),
         }
     }
 
+    // See fen.rs for the format. The position's history is lost, so ply_to_get_here is Root
+    pub fn from_fen(fen: &str, rules: RuleSet) -> Result<Self, FenError> {
+        let fen = fen::parse_fen(fen, &rules)?;
+        let current_player_id = fen.current_player_id;
+
+        let mut game_state = GameState {
+            current_player_id,
+            player1_state: fen.player1_state,
+            player2_state: fen.player2_state,
+            moves_since_mill: fen.moves_since_mill,
+            ..GameState::with_board(fen.board, rules)
+        };
+        game_state.next_ply = if fen.pending_removal {
+            Mill { player_id: current_player_id, piece_id: 0 }
+        } else if game_state.current_player_state().is_placement() {
+            Placement { player_id: current_player_id, piece_id: 0 }
+        } else {
+            Move { player_id: current_player_id, mv: (0, 0) }
+        };
+
+        Ok(game_state)
+    }
+
+    pub fn to_fen(&self) -> String {
+        fen::to_fen(self)
+    }
+
     pub fn last_player_has_won(&self) -> bool {
-        let new_player_state = self.current_player_state();
-        let last_player_state = self.other_player_state();
+        self.outcome().and_then(|o| o.winner()) == Some(self.current_player_id.other())
+    }
+
+    // How the game has ended, if it has. Only decided between turns, so always None while a
+    // piece is waiting to be removed. Repetitions aren't known here, see Game
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        if self.next_ply.is_mill() {
+            return None;
+        }
+
+        // Have to check for last player as by this point the players have swapped
+        let win_reason = self.other_player_state().win_reason(
+                &self.rules,
+                self.available_moves(self.current_player_id),
+                self.current_player_state());
+
+        match win_reason {
+            // With no empty points nobody can move, and a player with pieces in hand can't place them
+            Some(WinReason::NoMoves) | None if self.board.is_full() => Some(GameOutcome::Draw { reason: DrawReason::FullBoard }),
+            Some(reason) => Some(GameOutcome::Win { player: self.current_player_id.other(), reason }),
+            None if self.move_limit_reached() => Some(GameOutcome::Draw { reason: DrawReason::MoveLimit }),
+            None => None,
+        }
+    }
+
+    // Too many moves without a mill, so the game is a draw
+    pub fn move_limit_reached(&self) -> bool {
+        matches!(self.rules.move_limit, Some(limit) if self.moves_since_mill >= limit)
+    }
 
-        last_player_state.has_won(
-                self.board.available_moves(self.current_player_id), // moves for new/current player
-                new_player_state.is_placement())
+    pub fn moves_since_mill(&self) -> u16 {
+        self.moves_since_mill
     }
 
     pub fn children(&self) -> Vec<GameState> {
+        self.available_plies().into_iter()
+            .map(|ply| self.child(ply))
+            .collect()
+    }
+
+    // Whether ply can be made now. Everything in available_plies is legal, and nothing else is
+    pub fn is_legal(&self, ply: &Ply) -> Result<(), IllegalMoveReason> {
+        use game::IllegalMoveReason::*;
+
+        let expected = match self.next_ply {
+            Placement{..} => ExpectedPlacement,
+            Move{..} => ExpectedMove,
+            Mill{..} => ExpectedRemoval,
+            Root => panic!("Found Ply::{:?}", self.next_ply),
+        };
+        if mem::discriminant(ply) != mem::discriminant(&self.next_ply) {
+            return Err(expected);
+        }
+        if ply.player_id() != self.current_player_id {
+            return Err(NotYourTurn);
+        }
+
+        let player_id = self.current_player_id;
+        let point = |point: usize| if point < self.board.topology().len() { Ok(point) } else { Err(NoSuchPoint) };
+
+        match *ply {
+            Placement{piece_id, ..} => {
+                if !self.board.piece_at(point(piece_id)?).is_empty() {
+                    return Err(PointOccupied);
+                }
+            },
+            Move{mv: (from, to), ..} => {
+                let (from, to) = (point(from)?, point(to)?);
+                if !self.board.piece_at(from).owned_by(player_id) {
+                    return Err(NotYourPiece);
+                }
+                if !self.board.piece_at(to).is_empty() {
+                    return Err(PointOccupied);
+                }
+                if !self.is_flying(player_id) && !self.board.topology().are_connected(from, to) {
+                    return Err(NotConnected);
+                }
+            },
+            Mill{piece_id, ..} => {
+                if !self.board.piece_at(point(piece_id)?).owned_by(player_id.other()) {
+                    return Err(NotOpponentsPiece);
+                }
+                if !self.available_mills().contains(ply) {
+                    return Err(ProtectedByMill);
+                }
+            },
+            Root => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    // New GameState after making ply, of any kind
+    pub fn child(&self, ply: Ply) -> GameState {
+        let mut child = self.clone();
+        child.perform(ply);
+        child
+    }
+
+    pub fn try_child(&self, ply: Ply) -> Result<GameState, NinemanError> {
+        let mut child = self.clone();
+        child.try_perform(ply)?;
+        Ok(child)
+    }
+
+    // Every ply the current player can make from here
+    pub fn available_plies(&self) -> Vec<Ply> {
         assert!(self.next_ply.player_id() == self.current_player_id, "next_ply.player_id() should be same as current_player");
 
         // Could make all these calls to self.current_player_id just be in the methods?
         match self.next_ply {
-            Placement{..} =>
-                    self.board.available_places(self.current_player_id).into_iter()
-                        .map(|p| self.place_piece(p)).collect(),
-            Move{..} =>
-                    self.board.available_moves(self.current_player_id).into_iter()
-                        .map(|m| self.move_piece(m)).collect(),
-            Mill{..} =>
-                    self.board.available_mills(self.current_player_id, switch_player_id(self.current_player_id)).into_iter()
-                        .map(|m| self.mill_piece(m)).collect(),
+            Placement{..} => self.board.available_places(self.current_player_id),
+            Move{..} => self.available_moves(self.current_player_id),
+            Mill{..} => self.available_mills(),
             _ => panic!("Found Ply::{:?}", self.next_ply),
         }
     }
 
-    pub fn place_piece(&self, placement_ply: Ply) -> GameState {
+    // Every whole turn the current player can make, with a placement or move that makes a mill
+    // appearing once for each piece it could then remove. Only valid at the start of a turn
+    pub fn available_turns(&self) -> Vec<Turn> {
+        assert!(!self.next_ply.is_mill(), "Turns start with a placement or move, not {:?}", self.next_ply);
+
         let mut game_state = self.clone();
+        let mut turns = Vec::new();
+
+        for ply in self.available_plies() {
+            game_state.apply(&ply);
 
-        game_state.board.place_piece(placement_ply.clone());
-        game_state.ply_to_get_here = placement_ply;
+            let removals = if game_state.can_current_player_mill() { game_state.available_mills() } else { Vec::new() };
+            if removals.is_empty() {
+                turns.push(Turn::new(ply, None));
+            } else {
+                turns.extend(removals.into_iter().map(|removal| Turn::new(ply, Some(removal))));
+            }
 
-        let player_id = game_state.ply_to_get_here.player_id();
+            game_state.undo();
+        }
 
-        let new_player_state = game_state.player_state(player_id).place_piece();
-        game_state.update_player_state(player_id, new_player_state);
+        turns
+    }
 
-        give_new_game_state(&mut game_state);
+    pub fn turn_children(&self) -> Vec<GameState> {
+        self.available_turns().into_iter()
+            .map(|turn| {
+                let mut child = self.clone();
+                child.perform(turn.ply);
+                if let Some(removal) = turn.removal {
+                    child.perform(removal);
+                }
+                child
+            })
+            .collect()
+    }
 
-        game_state
+    // Apply both plies of a turn. Undo with undo_turn, or undo once per ply
+    pub fn apply_turn(&mut self, turn: &Turn) {
+        self.try_apply_turn(turn).unwrap_or_else(|e| panic!("{}", e));
     }
 
-    pub fn mill_piece(&self, mill_ply: Ply) -> GameState {
-        let mut game_state = self.clone();
+    // If the removal can't be made, the ply before it is undone too
+    pub fn try_apply_turn(&mut self, turn: &Turn) -> Result<(), NinemanError> {
+        self.try_apply(&turn.ply)?;
+        if let Some(removal) = turn.removal {
+            if let Err(e) = self.try_apply(&removal) {
+                self.undo();
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
 
-        game_state.board.perform_mill(mill_ply.clone());
-        game_state.ply_to_get_here = mill_ply;
+    pub fn undo_turn(&mut self) -> Option<Turn> {
+        let last = self.undo()?;
+        if last.is_mill() {
+            let ply = self.undo().expect("Mill with nothing before it to undo");
+            Some(Turn::new(ply, Some(last)))
+        } else {
+            Some(Turn::new(last, None))
+        }
+    }
 
-        give_new_game_state(&mut game_state);
+    pub fn place_piece(&self, placement_ply: Ply) -> GameState {
+        self.child(placement_ply)
+    }
 
-        game_state
+    pub fn mill_piece(&self, mill_ply: Ply) -> GameState {
+        self.child(mill_ply)
     }
 
     pub fn move_piece(&self, move_ply: Ply) -> GameState {
-        let mut game_state = self.clone();
+        self.child(move_ply)
+    }
+
+    // Make a ply in place, rather than creating a new GameState. Can be reversed with undo
+    pub fn apply(&mut self, ply: &Ply) {
+        self.try_apply(ply).unwrap_or_else(|e| panic!("{}", e));
+    }
 
-        game_state.board.move_piece(move_ply.clone());
-        game_state.ply_to_get_here = move_ply;
+    // Nothing is changed, or pushed for undo, if this returns an Err
+    pub fn try_apply(&mut self, ply: &Ply) -> Result<(), NinemanError> {
+        let undo = Undo {
+            ply: *ply,
+            ply_to_get_here: self.ply_to_get_here,
+            next_ply: self.next_ply,
+            current_player_id: self.current_player_id,
+            player1_state: self.player1_state.clone(),
+            player2_state: self.player2_state.clone(),
+            moves_since_mill: self.moves_since_mill,
+        };
+
+        self.try_perform(*ply)?;
+        self.undo_stack.push(undo);
+        Ok(())
+    }
 
-        give_new_game_state(&mut game_state);
+    // Reverse the last apply, returning the ply that was undone
+    pub fn undo(&mut self) -> Option<Ply> {
+        let undo = self.undo_stack.pop()?;
 
-        game_state
+        match undo.ply {
+            Placement{..} => self.board.undo_placement(&undo.ply),
+            Move{..} => self.board.undo_move(&undo.ply),
+            Mill{..} => self.board.undo_mill(&undo.ply),
+            Root => panic!("Can't undo Ply::Root"),
+        }
+
+        self.ply_to_get_here = undo.ply_to_get_here;
+        self.next_ply = undo.next_ply;
+        self.current_player_id = undo.current_player_id;
+        self.player1_state = undo.player1_state;
+        self.player2_state = undo.player2_state;
+        self.moves_since_mill = undo.moves_since_mill;
+
+        Some(undo.ply)
+    }
+
+    fn perform(&mut self, ply: Ply) {
+        self.try_perform(ply).unwrap_or_else(|e| panic!("{}", e));
+    }
+
+    // Each try_perform_ checks everything that can fail before changing anything
+    fn try_perform(&mut self, ply: Ply) -> Result<(), NinemanError> {
+        if mem::discriminant(&ply) != mem::discriminant(&self.next_ply) {
+            return Err(NinemanError::WrongKindOfPly(ply));
+        }
+        if ply.player_id() != self.current_player_id {
+            return Err(NinemanError::NotPlayersTurn(ply.player_id()));
+        }
+
+        match ply {
+            Placement{..} => self.try_perform_placement(ply),
+            Move{..} => self.try_perform_move(ply),
+            Mill{..} => self.try_perform_mill(ply),
+            Root => unreachable!(),
+        }
+    }
+
+    fn try_perform_placement(&mut self, placement_ply: Ply) -> Result<(), NinemanError> {
+        let player_id = placement_ply.player_id();
+        let new_player_state = self.player_state(player_id).try_place_piece()?;
+
+        self.board.try_place_piece(placement_ply)?;
+        self.ply_to_get_here = placement_ply;
+        self.update_player_state(player_id, new_player_state);
+
+        give_new_game_state(self);
+        Ok(())
+    }
+
+    fn try_perform_mill(&mut self, mill_ply: Ply) -> Result<(), NinemanError> {
+        let opponent_id = mill_ply.player_id().other();
+        let new_opponent_state = self.player_state(opponent_id).try_lose_piece()?;
+
+        self.board.try_perform_mill(mill_ply, self.rules.mill_removal)?;
+        self.ply_to_get_here = mill_ply;
+        self.update_player_state(opponent_id, new_opponent_state);
+        self.moves_since_mill = 0;
+
+        give_new_game_state(self);
+        Ok(())
+    }
+
+    fn try_perform_move(&mut self, move_ply: Ply) -> Result<(), NinemanError> {
+        let flying = self.is_flying(move_ply.player_id());
+
+        self.board.try_move_piece(move_ply, flying)?;
+        self.ply_to_get_here = move_ply;
+        self.moves_since_mill = self.moves_since_mill.saturating_add(1);
+
+        give_new_game_state(self);
+        Ok(())
+    }
+
+    // Zobrist hash of the position: pieces on the board, whose turn it is, pieces left to place,
+    // and whether the current player is about to remove a piece. The board's part is kept up to
+    // date as pieces move, the rest is a few lookups
+    pub fn hash(&self) -> u64 {
+        let mut hash = self.board.hash()
+            ^ zobrist::side_to_move(self.current_player_id)
+            ^ zobrist::hand(PlayerId::One, self.player1_state.pieces_left_to_place())
+            ^ zobrist::hand(PlayerId::Two, self.player2_state.pieces_left_to_place());
+
+        if self.can_current_player_mill() {
+            hash ^= zobrist::pending_removal();
+        }
+
+        hash
+    }
+
+    pub fn is_flying(&self, player_id: PlayerId) -> bool {
+        self.player_state(player_id).is_flying(self.rules.flying)
+    }
+
+    pub fn available_mills(&self) -> Vec<Ply> {
+        self.board.available_mills(self.current_player_id, self.rules.mill_removal)
+    }
+
+    pub fn available_moves(&self, player_id: PlayerId) -> Vec<Ply> {
+        self.board.available_moves(player_id, self.is_flying(player_id))
     }
 
-    pub fn can_current_player_mill(&mut self) -> bool {
+    pub fn can_current_player_mill(&self) -> bool {
         let player_id = self.current_player_id;
         self.next_ply.is_mill() && (self.next_ply.player_id() == player_id)
     }
 
-    pub fn can_mill_next(&mut self, player_id: i8) -> bool {
-        self.board.update_mills(player_id)
+    // Whether the placement or move that got here made a mill for player_id
+    pub fn can_mill_next(&self, player_id: PlayerId) -> bool {
+        match self.ply_to_get_here {
+            Placement{piece_id, ..} => self.board.forms_mill(player_id, piece_id),
+            Move{mv, ..} => self.board.forms_mill(player_id, mv.1),
+            _ => false,
+        }
     }
 
-    fn new_next_ply(&mut self, player_id: i8, can_mill: bool) {
-        let ply;
+    fn new_next_ply(&mut self, player_id: PlayerId, can_mill: bool) {
         // check if mill, player_id same
-        if can_mill {
-            ply = Mill {player_id: player_id, piece_id: "".to_string()};
+        let ply = if can_mill {
+            Mill {player_id, piece_id: 0}
         // if not mill, work out if placement, player_id is switched
         } else {
-            let other_player_id = switch_player_id(player_id);
+            let other_player_id = player_id.other();
 
             if self.player_state(other_player_id).is_placement() {
-                ply = Placement {player_id: other_player_id, piece_id: "".to_string()};
-                // if not placement, must be move, player_id is switched
+                Placement {player_id: other_player_id, piece_id: 0}
+                // if not placement, must be move (or flying move), player_id is switched
             } else {
-                ply = Move {player_id: other_player_id, mv: ("".to_string(), "".to_string())};
+                Move {player_id: other_player_id, mv: (0, 0)}
             }
-        }
+        };
 
         self.next_ply = ply;
     }
 
-    fn player_state(&self, player_id: i8) -> &PlayerState {
+    pub fn player_state(&self, player_id: PlayerId) -> &PlayerState {
         match player_id {
-            1 => &self.player1_state,
-            2 => &self.player2_state,
-            _ => panic!("invalid player_id {}", player_id),
+            PlayerId::One => &self.player1_state,
+            PlayerId::Two => &self.player2_state,
         }
     }
 
@@ -139,19 +459,18 @@ impl GameState {
     }
 
     fn other_player_state(&self) -> &PlayerState {
-        let other_id = switch_player_id(self.current_player_id);
+        let other_id = self.current_player_id.other();
         self.player_state(other_id)
     }
 
-    fn update_player_state(&mut self, player_id: i8, player_state: PlayerState) {
+    fn update_player_state(&mut self, player_id: PlayerId, player_state: PlayerState) {
         match player_id {
-            1 => self.player1_state = player_state,
-            2 => self.player2_state = player_state,
-            _ => panic!("Invalid player_id {}", player_id),
+            PlayerId::One => self.player1_state = player_state,
+            PlayerId::Two => self.player2_state = player_state,
         }
     }
 
-    pub fn player_score(&self, player_id: i8) -> i8 {
+    pub fn player_score(&self, player_id: PlayerId) -> i8 {
         self.player_state(player_id).score()
     }
 
@@ -168,8 +487,14 @@ impl GameState {
 // Could be better returning a GameState but doesn't make a huge difference
 fn give_new_game_state(game_state: &mut GameState) {
     let player_id = game_state.ply_to_get_here.player_id();
-    let can_mill = game_state.can_mill_next(player_id);
-    game_state.new_next_ply(player_id, can_mill);
+    // When a mill wins the game there's nothing to remove. With MillRemoval::NotFromMills there
+    // can be nothing the mill is allowed to take, so it doesn't count and the turn passes
+    let wins = game_state.rules.win_condition == WinCondition::Mill;
+    let mut can_mill = game_state.can_mill_next(player_id);
+    if can_mill && !wins {
+        can_mill = !game_state.board.available_mills(player_id, game_state.rules.mill_removal).is_empty();
+    }
+    game_state.new_next_ply(player_id, can_mill && !wins);
     game_state.current_player_id = game_state.next_ply.player_id();
 
     // If this is a placement/move that leads to a mill, increment the score
@@ -179,13 +504,394 @@ fn give_new_game_state(game_state: &mut GameState) {
     }
 }
 
+// Copies start with nothing to undo, as the plies on the undo stack were applied to the original.
+// This also keeps children from copying the whole stack
+impl Clone for GameState {
+    fn clone(&self) -> Self {
+        GameState {
+            ply_to_get_here: self.ply_to_get_here,
+            next_ply: self.next_ply,
+            board: self.board.clone(),
+            current_player_id: self.current_player_id,
+            player1_state: self.player1_state.clone(),
+            player2_state: self.player2_state.clone(),
+            rules: self.rules,
+            moves_since_mill: self.moves_since_mill,
+            undo_stack: Vec::new(),
+        }
+    }
+}
+
+// Two states are the same regardless of what can be undone from them
+impl PartialEq for GameState {
+    fn eq(&self, other: &GameState) -> bool {
+        self.ply_to_get_here == other.ply_to_get_here &&
+        self.next_ply == other.next_ply &&
+        self.board == other.board &&
+        self.current_player_id == other.current_player_id &&
+        self.player1_state == other.player1_state &&
+        self.player2_state == other.player2_state &&
+        self.rules == other.rules &&
+        self.moves_since_mill == other.moves_since_mill
+    }
+}
+
+impl Eq for GameState {}
+
 impl fmt::Debug for GameState {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 
-        return write!(f, "GameState(ply_to_get_here: {:?}, next_ply: {:?}, current_player: {}; p1: (s:{},p:{}), p2: (s:{},p:{}))",
-                    self.ply_to_get_here, self.next_ply,
-                    self.current_player_id,
-                    self.player1_state.score(), self.player1_state.pieces_left_to_place(),
-                    self.player2_state.score(), self.player2_state.pieces_left_to_place());
+        write!(f, "GameState(ply_to_get_here: {:?}, next_ply: {:?}, current_player: {}; p1: (s:{},p:{}), p2: (s:{},p:{}))",
+                self.ply_to_get_here, self.next_ply,
+                self.current_player_id,
+                self.player1_state.score(), self.player1_state.pieces_left_to_place(),
+                self.player2_state.score(), self.player2_state.pieces_left_to_place())
+    }
+}
+
+// Always taking the last turn gets through placement, mills and moves, so tests can use it for a
+// whole game. Stops early if the game ends
+#[cfg(test)]
+pub fn last_turns(rules: RuleSet, max_turns: usize) -> Vec<Turn> {
+    let mut game_state = GameState::at_beginning(rules);
+    let mut turns = Vec::new();
+
+    while turns.len() < max_turns && game_state.outcome().is_none() {
+        let turn = game_state.available_turns().pop().unwrap();
+        game_state.apply_turn(&turn);
+        turns.push(turn);
+    }
+    turns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::MillRemoval;
+    use player::PlayerId::{One, Two};
+
+    // Where id is on the Nine Men's Morris board, e.g. 0 for "0nw"
+    fn point(id: &str) -> usize {
+        board::generate_topology(board::BoardKind::NineMen).index_of(id).unwrap()
+    }
+
+    // Placement is over, and player 1 has three pieces on the outer square and player 2 four on
+    // the middle one. Player 1 to move
+    fn endgame(flying: bool) -> GameState {
+        let mut game_state = GameState::at_beginning(RuleSet::standard().with_flying(flying));
+        let pieces = [(One, "0nw"), (One, "0ne"), (One, "0se"), (Two, "1nw"), (Two, "1ne"), (Two, "1se"), (Two, "1sw")];
+        for &(player_id, id) in &pieces {
+            game_state.board.place_piece(Placement { player_id, piece_id: point(id) });
+        }
+        for _ in 0..9 {
+            game_state.player1_state = game_state.player1_state.place_piece();
+            game_state.player2_state = game_state.player2_state.place_piece();
+        }
+        for _ in 0..6 {
+            game_state.player1_state = game_state.player1_state.lose_piece();
+        }
+        for _ in 0..5 {
+            game_state.player2_state = game_state.player2_state.lose_piece();
+        }
+        game_state.next_ply = Move { player_id: One, mv: (0, 0) };
+        game_state
+    }
+
+    #[test]
+    fn flying_moves_to_any_empty_point() {
+        for &flying in &[true, false] {
+            let game_state = endgame(flying);
+            assert_eq!(game_state.is_flying(One), flying);
+            assert!(!game_state.is_flying(Two));
+
+            // Each piece can fly to any of the 17 empty points, or move to the two next to it
+            let moves = game_state.available_moves(One);
+            assert_eq!(moves.len(), if flying { 3 * 17 } else { 3 * 2 });
+            let far = Move { player_id: One, mv: (point("0nw"), point("2s")) };
+            assert_eq!(moves.contains(&far), flying);
+            assert_eq!(game_state.children().len(), moves.len());
+        }
+    }
+
+    #[test]
+    fn two_pieces_left_loses() {
+        let mut game_state = GameState::at_beginning(RuleSet { pieces_per_player: 3, ..RuleSet::standard() });
+        let placement = |player_id, id: &str| Placement { player_id, piece_id: point(id) };
+
+        // Player 1 makes a mill on the outer north side while player 2 has one piece still to place
+        for &(player_id, id) in &[(One, "0nw"), (Two, "1nw"), (One, "0n"), (Two, "1n"), (One, "0ne")] {
+            game_state = game_state.place_piece(placement(player_id, id));
+            assert!(!game_state.last_player_has_won());
+        }
+        game_state = game_state.mill_piece(Mill { player_id: One, piece_id: point("1nw") });
+
+        assert_eq!(game_state.player2_state.total_pieces(), 2);
+        assert!(game_state.last_player_has_won());
+    }
+
+    #[test]
+    fn legacy_rules_win_on_a_score_of_seven() {
+        for &(rules, winning_score) in &[(RuleSet::legacy(), Some(7)), (RuleSet::standard(), None)] {
+            let mut game_state = GameState::at_beginning(rules);
+            game_state.current_player_id = Two;
+
+            for score in 1..8 {
+                game_state.player1_state = game_state.player1_state.increment_score();
+                assert_eq!(game_state.last_player_has_won(), Some(score) == winning_score);
+            }
+        }
+    }
+
+    #[test]
+    fn mills_with_nothing_to_remove_pass_the_turn() {
+        let placement = |player_id, id: &str| Placement { player_id, piece_id: point(id) };
+
+        // Player 2's pieces are all in the 1n mill when player 1 makes the 0n mill
+        let plies = [
+            placement(One, "0nw"), placement(Two, "1nw"), placement(One, "0n"), placement(Two, "1n"),
+            placement(One, "2s"), placement(Two, "1ne"), Mill { player_id: Two, piece_id: point("2s") },
+            placement(One, "0ne"),
+        ];
+
+        for &mill_removal in &[MillRemoval::FromMillsIfNoOther, MillRemoval::NotFromMills, MillRemoval::AnyPiece] {
+            let mut game_state = GameState::at_beginning(RuleSet { mill_removal, ..RuleSet::standard() });
+            for ply in &plies {
+                game_state = match *ply {
+                    Mill{..} => game_state.mill_piece(*ply),
+                    _ => game_state.place_piece(*ply),
+                };
+            }
+
+            if mill_removal == MillRemoval::NotFromMills {
+                assert_eq!(game_state.next_ply, Placement { player_id: Two, piece_id: 0 });
+                assert_eq!(game_state.player_score(One), 0);
+            } else {
+                assert!(game_state.can_current_player_mill());
+                assert_eq!(game_state.children().len(), 3);
+                assert_eq!(game_state.player_score(One), 1);
+            }
+            assert!(!game_state.children().is_empty());
+            assert!(!game_state.last_player_has_won());
+        }
+    }
+
+    #[test]
+    fn first_mill_wins_three_mens_morris() {
+        let mut game_state = GameState::at_beginning(RuleSet::three_mens_morris());
+
+        // Player 1 takes the top row, 0nw to 0ne, while player 2 plays on the left
+        for &(player_id, piece_id) in &[(One, 0), (Two, 6), (One, 1), (Two, 7)] {
+            game_state = game_state.place_piece(Placement { player_id, piece_id });
+            assert!(!game_state.last_player_has_won());
+        }
+        game_state = game_state.place_piece(Placement { player_id: One, piece_id: 2 });
+
+        assert!(game_state.last_player_has_won());
+        assert!(!game_state.can_current_player_mill());
+        assert_eq!(game_state.player2_state.total_pieces(), 3);
+    }
+
+    #[test]
+    fn undo_reverses_apply() {
+        let beginning = GameState::at_beginning(RuleSet::standard().with_flying(true));
+        let mut game_state = beginning.clone();
+        let mut states = Vec::new();
+
+        for ply in last_turns(beginning.rules, 50).iter().flat_map(Turn::plies) {
+            states.push(game_state.clone());
+            game_state.apply(&ply);
+            assert_eq!(game_state, states.last().unwrap().children().into_iter()
+                .find(|c| c.ply_to_get_here == ply).unwrap());
+        }
+
+        while let Some(state) = states.pop() {
+            game_state.undo().unwrap();
+            assert_eq!(game_state, state);
+        }
+        assert_eq!(game_state, beginning);
+        assert_eq!(game_state.undo(), None);
+    }
+
+    #[test]
+    fn copies_have_nothing_to_undo() {
+        let mut game_state = GameState::at_beginning(RuleSet::standard());
+        game_state.apply(&Placement { player_id: One, piece_id: point("0nw") });
+
+        let mut child = game_state.place_piece(Placement { player_id: Two, piece_id: point("0se") });
+        assert_eq!(child.undo(), None);
+        assert!(game_state.children().into_iter().all(|mut child| child.undo().is_none()));
+        assert_eq!(game_state.clone().undo(), None);
+        assert!(game_state.undo().is_some());
+    }
+
+    #[test]
+    fn turns_include_removals() {
+        let mut game_state = GameState::at_beginning(RuleSet::standard());
+        // P1 on 0nw and 0n, P2 on 1nw and 1n: P1 can make a mill at 0ne and take either P2 piece
+        for &(player_id, piece_id) in &[(One, 0), (Two, 8), (One, 1), (Two, 9)] {
+            game_state.apply(&Placement { player_id, piece_id });
+        }
+
+        let turns = game_state.available_turns();
+        let mills: Vec<&Turn> = turns.iter().filter(|t| t.removal.is_some()).collect();
+        assert_eq!(turns.len(), 20 - 1 + 2);
+        assert_eq!(mills.len(), 2);
+        assert!(mills.iter().all(|t| t.ply == Placement { player_id: One, piece_id: 2 }));
+        assert_eq!(game_state.turn_children().len(), turns.len());
+
+        let before = game_state.clone();
+        game_state.apply_turn(mills[0]);
+        assert_eq!(game_state.current_player_id, Two);
+        assert_eq!(game_state.undo_turn(), Some(*mills[0]));
+        assert_eq!(game_state, before);
+    }
+
+    #[test]
+    fn hash_depends_on_position_not_order() {
+        let place = |game_state: &mut GameState, player_id, piece_id| game_state.apply(&Placement { player_id, piece_id });
+        let beginning = GameState::at_beginning(RuleSet::standard());
+
+        let mut first = beginning.clone();
+        for &(player_id, piece_id) in &[(One, 0), (Two, 8), (One, 4), (Two, 12)] {
+            place(&mut first, player_id, piece_id);
+        }
+        let mut second = beginning.clone();
+        for &(player_id, piece_id) in &[(One, 4), (Two, 12), (One, 0), (Two, 8)] {
+            place(&mut second, player_id, piece_id);
+        }
+        assert_eq!(first.hash(), second.hash());
+        assert!(first.hash() != beginning.hash());
+
+        place(&mut first, One, 16);
+        assert!(first.hash() != second.hash());
+
+        while first.undo().is_some() {}
+        assert_eq!(first.hash(), beginning.hash());
+    }
+
+    #[test]
+    fn move_limit_counts_moves_without_mills() {
+        let mut game_state = GameState::at_beginning(RuleSet::standard().with_move_limit(Some(4)));
+
+        // Avoid mills, so the counter is never reset
+        while !game_state.move_limit_reached() {
+            assert!(game_state.moves_since_mill() < 4);
+            let ply = game_state.available_plies().into_iter()
+                .find(|ply| !game_state.child(*ply).can_current_player_mill())
+                .unwrap();
+            game_state.apply(&ply);
+        }
+        assert_eq!(game_state.moves_since_mill(), 4);
+        assert_eq!(game_state.outcome(), Some(GameOutcome::Draw { reason: DrawReason::MoveLimit }));
+
+        game_state.undo();
+        assert_eq!(game_state.moves_since_mill(), 3);
+        assert!(!game_state.move_limit_reached());
+        assert_eq!(game_state.outcome(), None);
+    }
+
+    // Place wherever doesn't make a mill, while either player has pieces left to place
+    fn fill_board(rules: RuleSet) -> GameState {
+        let mut game_state = GameState::at_beginning(rules);
+        while game_state.outcome().is_none() {
+            let child = game_state.children().into_iter()
+                .find(|child| !child.can_current_player_mill())
+                .expect("Every placement makes a mill");
+            game_state = child;
+        }
+        game_state
+    }
+
+    #[test]
+    fn filling_the_board_is_a_draw() {
+        let game_state = fill_board(RuleSet::twelve_mens_morris());
+
+        assert!(game_state.board.is_full());
+        assert!(!game_state.current_player_state().is_placement());
+        assert_eq!(game_state.outcome(), Some(GameOutcome::Draw { reason: DrawReason::FullBoard }));
+        assert!(!game_state.last_player_has_won());
+    }
+
+    #[test]
+    fn filling_the_board_with_pieces_in_hand_is_a_draw() {
+        let game_state = fill_board(RuleSet { pieces_per_player: 9, ..RuleSet::six_mens_morris() });
+
+        assert!(game_state.board.is_full());
+        assert!(game_state.current_player_state().is_placement());
+        assert!(game_state.available_plies().is_empty());
+        assert_eq!(game_state.outcome(), Some(GameOutcome::Draw { reason: DrawReason::FullBoard }));
+    }
+
+    #[test]
+    fn bad_plies_are_errors_not_panics() {
+        let mut game_state = GameState::at_beginning(RuleSet::standard());
+        game_state.apply(&Placement { player_id: One, piece_id: 0 });
+        let before = game_state.clone();
+
+        let bad_plies = [
+            (Placement { player_id: One, piece_id: 1 }, NinemanError::NotPlayersTurn(One)),
+            (Placement { player_id: Two, piece_id: 0 }, NinemanError::PointOccupied(0)),
+            (Placement { player_id: Two, piece_id: 99 }, NinemanError::InvalidPoint(99)),
+            (Move { player_id: Two, mv: (1, 2) }, NinemanError::WrongKindOfPly(Move { player_id: Two, mv: (1, 2) })),
+            (Mill { player_id: Two, piece_id: 0 }, NinemanError::WrongKindOfPly(Mill { player_id: Two, piece_id: 0 })),
+            (Root, NinemanError::WrongKindOfPly(Root)),
+        ];
+        for &(ply, error) in &bad_plies {
+            assert_eq!(game_state.try_apply(&ply), Err(error));
+            assert_eq!(game_state, before);
+        }
+        assert_eq!(game_state.undo(), Some(Placement { player_id: One, piece_id: 0 }));
+    }
+
+    #[test]
+    fn removals_follow_the_rules() {
+        let mut game_state = GameState::at_beginning(RuleSet::standard());
+
+        // Player 2 makes a mill on 1n and takes 2s, then player 1 makes one on 0n
+        let plies = [
+            Placement { player_id: One, piece_id: point("0nw") }, Placement { player_id: Two, piece_id: point("1nw") },
+            Placement { player_id: One, piece_id: point("0n") }, Placement { player_id: Two, piece_id: point("1n") },
+            Placement { player_id: One, piece_id: point("2s") }, Placement { player_id: Two, piece_id: point("2e") },
+            Placement { player_id: One, piece_id: point("2w") }, Placement { player_id: Two, piece_id: point("1ne") },
+            Mill { player_id: Two, piece_id: point("2s") }, Placement { player_id: One, piece_id: point("0ne") },
+        ];
+        for ply in &plies {
+            game_state.apply(ply);
+        }
+        assert!(game_state.can_current_player_mill());
+        let before = game_state.clone();
+
+        // Only 2e isn't in a mill
+        let protected = Mill { player_id: One, piece_id: point("1n") };
+        assert_eq!(game_state.is_legal(&protected), Err(IllegalMoveReason::ProtectedByMill));
+        assert_eq!(game_state.try_apply(&protected), Err(NinemanError::InvalidRemoval { player_id: One, point: point("1n") }));
+
+        let placement = Placement { player_id: One, piece_id: point("2sw") };
+        assert_eq!(game_state.try_apply(&placement), Err(NinemanError::WrongKindOfPly(placement)));
+        assert_eq!(game_state, before);
+
+        assert!(game_state.try_apply(&Mill { player_id: One, piece_id: point("2e") }).is_ok());
+    }
+
+    #[test]
+    fn only_available_plies_are_legal() {
+        let rules = RuleSet::standard().with_flying(true);
+        let mut game_state = GameState::at_beginning(rules);
+
+        for ply in last_turns(rules, 50).iter().flat_map(Turn::plies) {
+            let available_plies = game_state.available_plies();
+
+            for &player_id in &PlayerId::both() {
+                for from in 0..25 {
+                    let mut plies = vec![Placement { player_id, piece_id: from }, Mill { player_id, piece_id: from }];
+                    plies.extend((0..25).map(|to| Move { player_id, mv: (from, to) }));
+                    for ply in plies {
+                        assert_eq!(game_state.is_legal(&ply).is_ok(), available_plies.contains(&ply), "{:?}", ply);
+                    }
+                }
+            }
+
+            game_state.apply(&ply);
+        }
     }
 }
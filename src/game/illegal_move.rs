@@ -0,0 +1,44 @@
+// Why GameState::is_legal turned down a ply
+
+use std::fmt;
+
+use self::IllegalMoveReason::*;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum IllegalMoveReason {
+    // The ply is the wrong kind for this point in the game
+    ExpectedPlacement,
+    ExpectedMove,
+    ExpectedRemoval,
+    NotYourTurn,
+    // Not the index of a point on the board
+    NoSuchPoint,
+    PointOccupied,
+    NotYourPiece,
+    // Pieces can only move along a connection, unless they're flying
+    NotConnected,
+    NotOpponentsPiece,
+    // Not allowed by the RuleSet's MillRemoval
+    ProtectedByMill,
+    // Only from Game::play, as GameState doesn't know about repetitions or resignations
+    GameOver,
+}
+
+impl fmt::Display for IllegalMoveReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match *self {
+            ExpectedPlacement => "a piece must be placed",
+            ExpectedMove => "a piece must be moved",
+            ExpectedRemoval => "a piece must be removed",
+            NotYourTurn => "it's not that player's turn",
+            NoSuchPoint => "that point isn't on the board",
+            PointOccupied => "that point already has a piece on it",
+            NotYourPiece => "that piece isn't yours",
+            NotConnected => "those points aren't connected",
+            NotOpponentsPiece => "only your opponent's pieces can be removed",
+            ProtectedByMill => "that piece is protected by a mill",
+            GameOver => "the game is over",
+        };
+        write!(f, "{}", reason)
+    }
+}
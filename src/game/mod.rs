@@ -1,14 +1,33 @@
+use std::collections::HashMap;
+use std::mem;
+
 use term_painter::Color::*;
 use term_painter::Painted;
 use term_painter::ToStyle;
 
+mod fen;
 mod game_state;
+mod illegal_move;
+mod outcome;
 mod ply;
+mod record;
+mod rule_set;
+mod turn;
 
 pub use self::game_state::GameState;
+pub use self::fen::FenError;
+pub use self::illegal_move::IllegalMoveReason;
+pub use self::outcome::{GameOutcome, WinReason, DrawReason};
 pub use self::ply::Ply;
+pub use self::record::{GameRecord, RecordError};
+pub use self::turn::Turn;
+pub use self::rule_set::{RuleSet, MillRemoval, WinCondition};
+
+// Illegal plies a player can make in a row before they forfeit the game
+pub const MAX_ILLEGAL_PLIES: u8 = 3;
 
-use player::Player;
+use player::{Player, PlayerId};
+use board;
 use board::Board;
 
 #[derive(Debug)]
@@ -16,18 +35,50 @@ pub struct Game {
     pub current_state: GameState,
     pub player1: Player,
     pub player2: Player,
+    // How many times each position has come up at the start of a turn, by GameState::hash
+    position_counts: HashMap<u64, u32>,
+    outcome: Option<GameOutcome>,
+    // Every ply so far, with the state it was played in. Undone plies wait in undone for redo,
+    // the next one to redo last
+    history: Vec<(GameState, Ply)>,
+    undone: Vec<Ply>,
+    // Where the history started from, for the GameRecord
+    start_fen: String,
 }
 
 impl Game {
     pub fn new(player1: Player, player2: Player) -> Self {
+        Game::with_rules(player1, player2, RuleSet::standard())
+    }
+
+    pub fn with_rules(player1: Player, player2: Player, rules: RuleSet) -> Self {
+        let board = board::build(rules.board);
+        Game::with_board(player1, player2, board, rules)
+    }
+
+    pub fn with_board(player1: Player, player2: Player, board: Board, rules: RuleSet) -> Self {
+        Game::from_state(player1, player2, GameState::with_board(board, rules))
+    }
+
+    // Start part way through a game, e.g. from GameState::from_fen. If a piece is waiting to be
+    // removed, the first turn is just the removal
+    pub fn from_state(player1: Player, player2: Player, state: GameState) -> Self {
         let mut game = Game {
-            current_state: GameState::at_beginning(),
-            player1: player1,
-            player2: player2,
+            start_fen: state.to_fen(),
+            current_state: state,
+            player1,
+            player2,
+            position_counts: HashMap::new(),
+            outcome: None,
+            history: Vec::new(),
+            undone: Vec::new(),
         };
+        if !game.current_state.can_current_player_mill() {
+            game.record_position();
+        }
         game.update_input_handlers();
-        game.player1.set_input_handler_player_id(1);
-        game.player2.set_input_handler_player_id(2);
+        game.player1.set_input_handler_player_id(PlayerId::One);
+        game.player2.set_input_handler_player_id(PlayerId::Two);
         game
     }
 
@@ -35,120 +86,279 @@ impl Game {
         self.current_state.print();
     }
 
-    pub fn game_loop(&mut self) -> i8 {
-        loop {
+    pub fn game_loop(&mut self) -> GameOutcome {
+        let outcome = loop {
+            if let Some(outcome) = self.position_outcome() {
+                break outcome;
+            }
+
             self.print();
 
-            self.current_state = self.make_move();
-            self.update_input_handlers();
+            if self.get_current_player_mut().resigns() {
+                break GameOutcome::Win { player: self.get_other_player_id(), reason: WinReason::Resignation };
+            }
+
+            match self.take_turn() {
+                Ok(turn) => println!("Player {} played {}", turn.player_id(), turn.to_algebraic(self.board())),
+                Err(_) => break GameOutcome::Win { player: self.get_other_player_id(), reason: WinReason::IllegalMoves },
+            }
+        };
+
+        self.outcome = Some(outcome);
+        self.end_game(outcome);
+        outcome
+    }
+
+    // How the game ended, if it has. Resignations and forfeits are only known once game_loop has
+    // finished, anything else is worked out from the current position
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        self.outcome.or_else(|| self.position_outcome())
+    }
+
+    // Whether the current position ends the game, from the position itself or repetitions
+    fn position_outcome(&self) -> Option<GameOutcome> {
+        let repetitions = self.position_counts.get(&self.current_state.hash()).cloned().unwrap_or(0);
 
-            self.current_state = self.mill();
+        self.current_state.outcome().or(
+            if matches!(self.current_state.rules.repetition_limit, Some(limit) if repetitions >= u32::from(limit)) {
+                Some(GameOutcome::Draw { reason: DrawReason::Repetition })
+            } else {
+                None
+            })
+    }
 
-            // Have to check for last player as by this point the players have swapped
-            if self.current_state.last_player_has_won() {
-                break
+    // The last turn is missing its removal if one is still to be made
+    pub fn turns(&self) -> Vec<Turn> {
+        let mut turns: Vec<Turn> = Vec::new();
+        for &(_, ply) in &self.history {
+            match ply {
+                Ply::Mill{..} if !turns.is_empty() => turns.last_mut().unwrap().removal = Some(ply),
+                _ => turns.push(Turn::new(ply, None)),
             }
+        }
+        turns
+    }
 
-            self.update_input_handlers();
+    pub fn plies(&self) -> Vec<Ply> {
+        self.history.iter().map(|&(_, ply)| ply).collect()
+    }
+
+    // Make a single ply, for UIs that run the game themselves rather than using game_loop.
+    // Anything undone can't be redone after this
+    pub fn play(&mut self, ply: Ply) -> Result<(), IllegalMoveReason> {
+        if self.outcome().is_some() {
+            return Err(IllegalMoveReason::GameOver);
+        }
+        self.current_state.is_legal(&ply)?;
+        self.undone.clear();
+        self.push_ply(ply);
+        Ok(())
+    }
+
+    // Take back the last turn, or just its placement or move if the removal hasn't been made yet.
+    // False if there's nothing to undo
+    pub fn undo(&mut self) -> bool {
+        if self.history.is_empty() {
+            return false;
+        }
+        if !self.current_state.can_current_player_mill() {
+            self.forget_position();
         }
 
-        self.end_game()
+        while let Some((game_state, ply)) = self.history.pop() {
+            self.current_state = game_state;
+            self.undone.push(ply);
+            if !ply.is_mill() {
+                break;
+            }
+        }
+
+        self.outcome = None;
+        self.update_input_handlers();
+        true
+    }
+
+    // Play the last undone turn again. False if there's nothing to redo
+    pub fn redo(&mut self) -> bool {
+        let ply = match self.undone.pop() {
+            Some(ply) => ply,
+            None => return false,
+        };
+        self.push_ply(ply);
+
+        if self.current_state.can_current_player_mill() && matches!(self.undone.last(), Some(p) if p.is_mill()) {
+            let removal = self.undone.pop().unwrap();
+            self.push_ply(removal);
+        }
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    // The game so far, to save with GameRecord's to_string. None for games on custom boards, as
+    // records only know the built in boards
+    pub fn record(&self) -> Option<GameRecord> {
+        let rules = self.current_state.rules;
+        if *self.board().topology() != board::generate_topology(rules.board) {
+            return None;
+        }
+        let at_beginning = self.start_fen == GameState::at_beginning(rules).to_fen();
+
+        Some(GameRecord {
+            outcome: self.outcome(),
+            fen: if at_beginning { None } else { Some(self.start_fen.clone()) },
+            turns: self.turns(),
+            ..GameRecord::new(self.player1.name.clone(), self.player2.name.clone(), rules)
+        })
+    }
+
+    // Ply has to be legal. Positions are counted at the start of each turn
+    fn push_ply(&mut self, ply: Ply) {
+        let child = self.current_state.child(ply);
+        let game_state = mem::replace(&mut self.current_state, child);
+        self.history.push((game_state, ply));
+
+        if !self.current_state.can_current_player_mill() {
+            self.record_position();
+        }
+        self.update_input_handlers();
+    }
+
+    // Count the current position, for position_outcome
+    fn record_position(&mut self) {
+        *self.position_counts.entry(self.current_state.hash()).or_insert(0) += 1;
+    }
+
+    fn forget_position(&mut self) {
+        let hash = self.current_state.hash();
+        if let Some(count) = self.position_counts.get_mut(&hash) {
+            *count -= 1;
+            if *count == 0 {
+                self.position_counts.remove(&hash);
+            }
+        }
     }
 
     fn update_input_handlers(&mut self) {
-        self.update_input_handler_for(1);
-        self.update_input_handler_for(2);
+        for &player_id in &PlayerId::both() {
+            self.update_input_handler_for(player_id);
+        }
     }
 
-    fn update_input_handler_for(&mut self, player_id: i8) {
+    fn update_input_handler_for(&mut self, player_id: PlayerId) {
         let game_state = self.current_state.clone();
+        let plies = self.plies();
         let player = self.get_player_mut(player_id);
         player.give_new_game_state(game_state);
+        player.give_plies(plies);
     }
 
-    fn mill(&mut self) -> GameState {
+    // A placement or move, then a mill if that made one. Err if the current player keeps
+    // making illegal plies. Games started waiting for a removal begin with just the removal
+    fn take_turn(&mut self) -> Result<Turn, IllegalMoveReason> {
         if self.current_state.can_current_player_mill() {
-            self.board().print();
-            let available_mills = self.board()
-                .available_mills(self.get_current_player_id(), self.get_other_player_id());
-            let mill_ply = self.get_current_player_mut().mill(available_mills);
-            self.current_state.mill_piece(mill_ply)
-        } else {
-            self.current_state.clone()
+            return Ok(Turn::new(self.get_mill()?, None));
         }
+
+        let ply = self.make_move()?;
+        let removal = if self.current_state.can_current_player_mill() {
+            Some(self.get_mill()?)
+        } else {
+            None
+        };
+        Ok(Turn::new(ply, removal))
     }
 
-    // TODO: maybe start passing in current_state rather than modifying it directly
-    fn make_move(&mut self) -> GameState {
+    fn get_mill(&mut self) -> Result<Ply, IllegalMoveReason> {
+        self.board().print();
+        let available_mills = self.current_state.available_mills();
+        self.get_legal_ply(|player| player.mill(available_mills.clone()))
+    }
+
+    fn make_move(&mut self) -> Result<Ply, IllegalMoveReason> {
         if self.current_state.current_player_state().is_placement() {
-            let placement = self.get_placement();
-            self.current_state.place_piece(placement)
+            self.get_placement()
         } else {
-            let mv = self.get_move();
-            self.current_state.move_piece(mv)
+            self.get_move()
         }
     }
 
-    fn end_game(&self) -> i8 {
-        let winner = self.get_other_player();
-        let loser = self.get_current_player();
+    // Input handlers can't be trusted to pick from what they're given, so check every ply and ask
+    // again if it's illegal, up to MAX_ILLEGAL_PLIES times. The legal ply is played
+    fn get_legal_ply<F>(&mut self, ask: F) -> Result<Ply, IllegalMoveReason>
+        where F: Fn(&mut Player) -> Ply {
 
-        println!("\nCongratulations, {} 🎉 (Player {})! You win with a score of {}",
-            self.colour_player(winner), winner.id, self.current_state.player_score(winner.id));
-        println!("Commiserations, {} 😞 (Player {}). You lose with a score of {}",
-            self.colour_player(loser), loser.id, self.current_state.player_score(loser.id));
+        let mut illegal_plies = 0;
+        loop {
+            let ply = ask(self.get_current_player_mut());
+            match self.play(ply) {
+                Ok(()) => return Ok(ply),
+                Err(reason) => {
+                    println!("Illegal ply {:?}: {}", ply, reason);
+                    illegal_plies += 1;
+                    if illegal_plies == MAX_ILLEGAL_PLIES {
+                        return Err(reason);
+                    }
+                },
+            }
+        }
+    }
 
-        winner.id
+    fn end_game(&self, outcome: GameOutcome) {
+        match outcome {
+            GameOutcome::Win{player, reason} => {
+                let winner = self.get_player(player);
+                let loser = self.get_player(player.other());
+
+                println!("\nCongratulations, {} 🎉 (Player {})! You win with a score of {} ({})",
+                    self.colour_player(winner), winner.id, self.current_state.player_score(winner.id), reason);
+                println!("Commiserations, {} 😞 (Player {}). You lose with a score of {}",
+                    self.colour_player(loser), loser.id, self.current_state.player_score(loser.id));
+            },
+            GameOutcome::Draw{reason} => {
+                self.print();
+                println!("\nDraw! ({})", reason);
+            },
+        }
     }
 
     fn colour_player(&self, player: &Player) -> Painted<String> {
         match player.id {
-            1 => Green.paint(player.name.to_owned()),
-            2 => Blue.paint(player.name.to_owned()),
-            _ => panic!("Unknown player id: {}", player.id),
+            PlayerId::One => Green.paint(player.name.to_owned()),
+            PlayerId::Two => Blue.paint(player.name.to_owned()),
         }
     }
 
-    fn get_move(&mut self) -> Ply {
-        let available_moves = self.board().available_moves(self.get_current_player_id());
+    fn get_move(&mut self) -> Result<Ply, IllegalMoveReason> {
+        let available_moves = self.current_state.available_moves(self.get_current_player_id());
 
-        let player = self.get_current_player_mut();
-
-        player.get_move(available_moves)
+        self.get_legal_ply(|player| player.get_move(available_moves.clone()))
     }
 
-    fn get_placement(&mut self) -> Ply {
+    fn get_placement(&mut self) -> Result<Ply, IllegalMoveReason> {
         let available_places = self.board().available_places(self.get_current_player_id());
 
-        let player = self.get_current_player_mut();
-
-        player.get_placement(available_places)
-    }
-
-    fn render_current_move(&self) -> String {
-        let mv;
-        if self.current_state.current_player_state().is_placement() {
-            mv = "place";
-        } else {
-            mv = "move";
-        }
-
-        format!("P{} to {}:", self.get_current_player_id(), mv)
+        self.get_legal_ply(|player| player.get_placement(available_places.clone()))
     }
 
-    pub fn get_current_player_id(&self) -> i8 {
+    pub fn get_current_player_id(&self) -> PlayerId {
         self.current_state.current_player_id
     }
 
-    pub fn get_other_player_id(&self) -> i8 {
-        switch_player_id(self.current_state.current_player_id)
+    pub fn get_other_player_id(&self) -> PlayerId {
+        self.current_state.current_player_id.other()
     }
 
-    pub fn get_player_mut(&mut self, player_id: i8) -> &mut Player {
+    pub fn get_player_mut(&mut self, player_id: PlayerId) -> &mut Player {
         match player_id {
-            1 => &mut self.player1,
-            2 => &mut self.player2,
-            _ => panic!("Invalid player id: {}", self.get_current_player_id()),
+            PlayerId::One => &mut self.player1,
+            PlayerId::Two => &mut self.player2,
         }
     }
 
@@ -157,19 +367,10 @@ impl Game {
         self.get_player_mut(player_id)
     }
 
-    fn get_current_player(&self) -> &Player {
-        match self.get_current_player_id() {
-            1 => &self.player1,
-            2 => &self.player2,
-            _ => panic!("Invalid player id: {}", self.get_current_player_id()),
-        }
-    }
-
-    fn get_other_player(&self) -> &Player {
-        match self.get_current_player_id() {
-            2 => &self.player1,
-            1 => &self.player2,
-            _ => panic!("Invalid player id: {}", self.get_current_player_id()),
+    fn get_player(&self, player_id: PlayerId) -> &Player {
+        match player_id {
+            PlayerId::One => &self.player1,
+            PlayerId::Two => &self.player2,
         }
     }
 
@@ -178,10 +379,202 @@ impl Game {
     }
 }
 
-pub fn switch_player_id(player_id: i8) -> i8 {
-    match player_id {
-        1 => 2,
-        2 => 1,
-        _ => panic!("invalid player_id {}", player_id),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use board::Cell;
+    use game::game_state::last_turns;
+    use player::{InputHandler, Random};
+
+    // Places its pieces in order, then plays the same moves over and over
+    struct Script {
+        placements: Vec<Ply>,
+        moves: Vec<Ply>,
+        next: usize,
+    }
+
+    impl InputHandler for Script {
+        fn give_new_game_state(&mut self, _game_state: GameState) {}
+
+        fn get_placement(&mut self, _available_places: Vec<Ply>) -> Ply {
+            self.placements.remove(0)
+        }
+
+        fn get_move(&mut self, _available_moves: Vec<Ply>) -> Ply {
+            self.next += 1;
+            self.moves[(self.next - 1) % self.moves.len()]
+        }
+
+        fn get_mill(&mut self, _available_mills: Vec<Ply>) -> Ply {
+            panic!("Script never makes mills")
+        }
+
+        fn to_string(&self) -> String {
+            "Script InputHandler".to_string()
+        }
+
+        fn set_player_id(&mut self, _player_id: PlayerId) {}
+    }
+
+    // Each player places three pieces, then moves one back and forth, a7-d7 and a1-d1, without making mills
+    fn shuffling_game(rules: RuleSet) -> Game {
+        let script = |player_id, points: [usize; 3], a, b| Box::new(Script {
+            placements: points.iter().map(|&piece_id| Ply::Placement { player_id, piece_id }).collect(),
+            moves: vec![Ply::Move { player_id, mv: (a, b) }, Ply::Move { player_id, mv: (b, a) }],
+            next: 0,
+        });
+        let player1 = Player::new("A".to_string(), PlayerId::One, script(PlayerId::One, [0, 4, 16], 0, 1));
+        let player2 = Player::new("B".to_string(), PlayerId::Two, script(PlayerId::Two, [2, 6, 20], 6, 5));
+        Game::with_rules(player1, player2, RuleSet { pieces_per_player: 3, ..rules })
+    }
+
+    #[test]
+    fn repeating_a_position_three_times_is_a_draw() {
+        let mut game = shuffling_game(RuleSet::standard());
+
+        assert_eq!(game.game_loop(), GameOutcome::Draw { reason: DrawReason::Repetition });
+        let repeated = game.current_state.hash();
+        assert_eq!(game.position_counts[&repeated], 3);
+        assert_eq!(game.board().piece_at(0), Cell::Piece(PlayerId::One));
+        assert_eq!(game.board().piece_at(6), Cell::Piece(PlayerId::Two));
+
+        // Taking back the last turn means the position has only come up twice
+        assert!(game.undo());
+        assert_eq!(game.position_counts[&repeated], 2);
+        assert_eq!(game.outcome(), None);
+        assert_eq!(game.position_outcome(), None);
+
+        assert!(game.redo());
+        assert_eq!(game.position_counts[&repeated], 3);
+        assert_eq!(game.position_outcome(), Some(GameOutcome::Draw { reason: DrawReason::Repetition }));
+    }
+
+    #[test]
+    fn repetitions_can_be_allowed() {
+        let mut game = shuffling_game(RuleSet::standard().with_repetition_limit(None).with_move_limit(Some(20)));
+
+        assert_eq!(game.game_loop(), GameOutcome::Draw { reason: DrawReason::MoveLimit });
+        assert_eq!(game.current_state.moves_since_mill(), 20);
+    }
+
+    fn game() -> Game {
+        game_from(GameState::at_beginning(RuleSet::standard()))
+    }
+
+    fn game_from(state: GameState) -> Game {
+        let player1 = Player::new("A".to_string(), PlayerId::One, Box::new(Random {}));
+        let player2 = Player::new("B".to_string(), PlayerId::Two, Box::new(Random {}));
+        Game::from_state(player1, player2, state)
+    }
+
+    #[test]
+    fn no_plies_after_the_game_is_over() {
+        let mut game = shuffling_game(RuleSet::standard());
+        game.game_loop();
+        assert_eq!(game.play(Ply::Move { player_id: PlayerId::One, mv: (0, 1) }), Err(IllegalMoveReason::GameOver));
+
+        // Undoing the last turn means the game isn't over any more
+        assert!(game.undo());
+        assert_eq!(game.play(Ply::Move { player_id: PlayerId::Two, mv: (5, 6) }), Ok(()));
+
+        // Finished without game_loop
+        let mut game = game_from(GameState::from_fen("111.22../. 2 0/1 - 1/0 0", RuleSet::three_mens_morris()).unwrap());
+        assert_eq!(game.outcome(), Some(GameOutcome::Win { player: PlayerId::One, reason: WinReason::ScoreReached }));
+        assert_eq!(game.play(Ply::Placement { player_id: PlayerId::Two, piece_id: 3 }), Err(IllegalMoveReason::GameOver));
+    }
+
+    #[test]
+    fn only_built_in_boards_are_recorded() {
+        assert!(game().record().is_some());
+
+        // The Six Men's Morris board, but not played with its rules
+        let layout = board::build(board::BoardKind::SixMen).to_layout_string();
+        let board = Board::from_layout_str(&layout).unwrap();
+        let rules = RuleSet { pieces_per_player: 6, ..RuleSet::standard() };
+        assert!(game_from(GameState::with_board(board, rules)).record().is_none());
+    }
+
+    #[test]
+    fn finished_positions_end_straight_away() {
+        // Player 1 has made a mill in Three Men's Morris
+        let state = GameState::from_fen("111.22../. 2 0/1 - 1/0 0", RuleSet::three_mens_morris()).unwrap();
+        let mut game = game_from(state);
+
+        assert_eq!(game.game_loop(), GameOutcome::Win { player: PlayerId::One, reason: WinReason::ScoreReached });
+        assert!(game.turns().is_empty());
+    }
+
+    #[test]
+    fn games_can_start_waiting_for_a_removal() {
+        // Player 1 has just made a mill on the outer north side
+        let fen = "111...../22....../........ 1 6/7 x 1/0 0";
+        let mut game = game_from(GameState::from_fen(fen, RuleSet::standard()).unwrap());
+        game.game_loop();
+
+        let turns = game.turns();
+        assert!(turns[0].ply.is_mill() && turns[0].removal.is_none());
+        assert_eq!(turns[0].player_id(), PlayerId::One);
+
+        let record = GameRecord::parse(&game.record().unwrap().to_string()).unwrap();
+        assert_eq!(record.fen, Some(fen.to_string()));
+        assert_eq!(record.replay().unwrap().last().unwrap().to_fen(), game.current_state.to_fen());
+    }
+
+    fn turns_to_first_mill() -> Vec<Turn> {
+        let turns = last_turns(RuleSet::standard(), 50);
+        let mill = turns.iter().position(|t| t.removal.is_some()).unwrap();
+        turns[..=mill].to_vec()
+    }
+
+    #[test]
+    fn undo_and_redo_turns() {
+        let mut game = game();
+        let beginning = game.current_state.clone();
+        assert!(!game.undo());
+
+        // Up to and including the first mill
+        let mut states = vec![beginning.clone()];
+        for turn in turns_to_first_mill() {
+            for ply in turn.plies() {
+                game.play(ply).unwrap();
+            }
+            states.push(game.current_state.clone());
+        }
+        let turns = game.turns();
+        assert_eq!(game.plies().len(), turns.len() + 1);
+
+        assert!(game.undo());
+        assert_eq!(game.current_state, states[states.len() - 2]);
+        assert_eq!(game.turns(), turns[..turns.len() - 1]);
+        assert!(game.redo());
+        assert_eq!(game.current_state, *states.last().unwrap());
+        assert_eq!(game.turns(), turns);
+        assert!(!game.redo());
+
+        while game.undo() {}
+        assert_eq!(game.current_state, beginning);
+        assert_eq!(game.position_counts.len(), 1);
+        while game.redo() {}
+        assert_eq!(game.turns(), turns);
+    }
+
+    #[test]
+    fn undo_pending_removal() {
+        let mut game = game();
+        let mut turns = turns_to_first_mill();
+        let mill_turn = turns.pop().unwrap();
+        for ply in turns.iter().flat_map(Turn::plies) {
+            game.play(ply).unwrap();
+        }
+        let before = game.current_state.clone();
+
+        game.play(mill_turn.ply).unwrap();
+        assert!(game.current_state.can_current_player_mill());
+        assert_eq!(game.play(mill_turn.ply), Err(IllegalMoveReason::ExpectedRemoval));
+
+        assert!(game.undo());
+        assert_eq!(game.current_state, before);
+        assert!(game.redo());
+        assert!(game.current_state.can_current_player_mill());
     }
 }
@@ -0,0 +1,85 @@
+// How a game ended. GameState::outcome knows about wins and the move limit, Game adds
+// repetitions and resignations, which need more than the current position
+
+use std::fmt;
+
+use player::PlayerId;
+
+use self::GameOutcome::*;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum GameOutcome {
+    Win{player: PlayerId, reason: WinReason},
+    Draw{reason: DrawReason},
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum WinReason {
+    // The loser had no moves left
+    NoMoves,
+    // WinCondition::TooFewPieces
+    TooFewPieces,
+    // WinCondition::Score, or WinCondition::Mill (a score of one)
+    ScoreReached,
+    // See InputHandler::resigns
+    Resignation,
+    // The loser made game::MAX_ILLEGAL_PLIES illegal plies in a row
+    IllegalMoves,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum DrawReason {
+    // See RuleSet::repetition_limit
+    Repetition,
+    // See RuleSet::move_limit
+    MoveLimit,
+    // Nobody can move or place, e.g. when placement fills a Twelve Men's Morris board
+    FullBoard,
+}
+
+impl GameOutcome {
+    // None for a draw
+    pub fn winner(&self) -> Option<PlayerId> {
+        match *self {
+            Win{player, ..} => Some(player),
+            Draw{..} => None,
+        }
+    }
+
+    pub fn is_draw(&self) -> bool {
+        matches!(*self, Draw{..})
+    }
+}
+
+impl fmt::Display for GameOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Win{player, reason} => write!(f, "Player {} wins: {}", player, reason),
+            Draw{reason} => write!(f, "Draw: {}", reason),
+        }
+    }
+}
+
+impl fmt::Display for WinReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match *self {
+            WinReason::NoMoves => "opponent has no moves left",
+            WinReason::TooFewPieces => "opponent has too few pieces left",
+            WinReason::ScoreReached => "winning score reached",
+            WinReason::Resignation => "opponent resigned",
+            WinReason::IllegalMoves => "opponent made too many illegal moves",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+impl fmt::Display for DrawReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match *self {
+            DrawReason::Repetition => "the same position came up too many times",
+            DrawReason::MoveLimit => "too many moves without a mill",
+            DrawReason::FullBoard => "the board is full",
+        };
+        write!(f, "{}", reason)
+    }
+}
@@ -1,45 +1,121 @@
 // A Ply is a "move", aka layer of game tree or each choice by a player
 // See: https://en.wikipedia.org/wiki/Ply_(game_theory)
+// piece_ids are indices of points on the board, so plies are Copy and cheap to generate.
+// Use to_notation/from_notation to convert to and from the position ids, e.g. "0n" or "0n,0e", and
+// to_algebraic/from_algebraic for algebraic notation, e.g. "d7", "a1-a4" or "xd7" for a removal
+
+use board::Board;
+use error::NinemanError;
+use player::PlayerId;
 
 use self::Ply::*;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum Ply {
     Root,
-    Placement{player_id: i8, piece_id: String},
-    Mill{player_id: i8, piece_id: String},
-    Move{player_id: i8, mv: (String, String)},
+    Placement{player_id: PlayerId, piece_id: usize},
+    Mill{player_id: PlayerId, piece_id: usize},
+    Move{player_id: PlayerId, mv: (usize, usize)},
 }
 
 impl Ply {
-    pub fn player_id(&self) -> i8 {
+    pub fn player_id(&self) -> PlayerId {
+        self.try_player_id().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Err for Root, which no player made
+    pub fn try_player_id(&self) -> Result<PlayerId, NinemanError> {
         match *self {
-            Root => 0,
-            Placement{ref player_id, ..} => player_id.to_owned(),
-            Mill{ref player_id, ..} => player_id.to_owned(),
-            Move{ref player_id, ..} => player_id.to_owned(),
+            Root => Err(NinemanError::WrongKindOfPly(*self)),
+            Placement{player_id, ..} => Ok(player_id),
+            Mill{player_id, ..} => Ok(player_id),
+            Move{player_id, ..} => Ok(player_id),
         }
     }
 
-    pub fn piece_id(&self) -> String {
+    pub fn piece_id(&self) -> usize {
+        self.try_piece_id().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Err for plies without a piece_id, i.e. Root and Move
+    pub fn try_piece_id(&self) -> Result<usize, NinemanError> {
         match *self {
-            Placement{ref piece_id, ..} => piece_id.to_owned(),
-            Mill{ref piece_id, ..} => piece_id.to_owned(),
-            _ => panic!("Ply does not contain field piece_id!"),
+            Placement{piece_id, ..} => Ok(piece_id),
+            Mill{piece_id, ..} => Ok(piece_id),
+            _ => Err(NinemanError::WrongKindOfPly(*self)),
         }
     }
 
-    pub fn mv(&self) -> (String, String) {
+    pub fn mv(&self) -> (usize, usize) {
+        self.try_mv().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_mv(&self) -> Result<(usize, usize), NinemanError> {
         match *self {
-            Move{ref mv, ..} => mv.to_owned(),
-            _ => panic!("Ply is not a move!"),
+            Move{mv, ..} => Ok(mv),
+            _ => Err(NinemanError::WrongKindOfPly(*self)),
         }
     }
 
     pub fn is_mill(&self) -> bool {
+        matches!(*self, Mill{..})
+    }
+
+    pub fn to_notation(&self, board: &Board) -> String {
+        match *self {
+            Root => "root".to_string(),
+            Placement{piece_id, ..} | Mill{piece_id, ..} => board.get_id(piece_id),
+            Move{mv: (from, to), ..} => format!("{},{}", board.get_id(from), board.get_id(to)),
+        }
+    }
+
+    pub fn to_algebraic(&self, board: &Board) -> String {
+        match *self {
+            Root => "root".to_string(),
+            Placement{piece_id, ..} => board.square(piece_id),
+            Mill{piece_id, ..} => format!("x{}", board.square(piece_id)),
+            Move{mv: (from, to), ..} => format!("{}-{}", board.square(from), board.square(to)),
+        }
+    }
+
+    // Parse algebraic notation into the same kind of ply as self, like from_notation. The x
+    // before a removal is optional
+    pub fn from_algebraic(&self, notation: &str, board: &Board) -> Option<Ply> {
+        let point = |square: &str| board.point_at_square(square);
+
+        match *self {
+            Root => None,
+            Placement{player_id, ..} => point(notation).map(|piece_id| Placement { player_id, piece_id }),
+            Mill{player_id, ..} => {
+                let notation = notation.trim();
+                point(notation.strip_prefix('x').unwrap_or(notation)).map(|piece_id| Mill { player_id, piece_id })
+            },
+            Move{player_id, ..} => {
+                let mut points = notation.split('-').map(point);
+                match (points.next(), points.next(), points.next()) {
+                    (Some(Some(from)), Some(Some(to)), None) => Some(Move { player_id, mv: (from, to) }),
+                    _ => None,
+                }
+            },
+        }
+    }
+
+    // Parse notation into the same kind of ply as self, e.g. "0n,0e" for a Move. None if it
+    // doesn't name points on board
+    pub fn from_notation(&self, notation: &str, board: &Board) -> Option<Ply> {
+        let point = |id: &str| board.topology().index_of(id.trim());
+
         match *self {
-            Mill{..} => true,
-            _ => false,
+            Root => None,
+            Placement{player_id, ..} => point(notation).map(|piece_id| Placement { player_id, piece_id }),
+            Mill{player_id, ..} => point(notation).map(|piece_id| Mill { player_id, piece_id }),
+            Move{player_id, ..} => {
+                let mut points = notation.split(',').map(point);
+                match (points.next(), points.next(), points.next()) {
+                    (Some(Some(from)), Some(Some(to)), None) => Some(Move { player_id, mv: (from, to) }),
+                    _ => None,
+                }
+            },
         }
     }
 }
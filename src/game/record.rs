@@ -0,0 +1,375 @@
+// A saved game, in a format like chess's PGN: headers in square brackets, then the turns in
+// algebraic notation, numbered every two turns, and the result.
+//
+//   [Player1 "Dave"]
+//   [Player2 "Bertie"]
+//   [Date "2026.10.18"]
+//   [Board "NineMen"]
+//   ...
+//   [Result "1-0"]
+//   [Termination "TooFewPieces"]
+//
+//   1. d7 d6 2. a7 b6 3. g7xb6 ... 1-0
+//
+// Unknown headers are ignored, and missing rules headers are the standard rules. Only the
+// built in boards can be recorded, as the board is saved as its BoardKind. Games that didn't
+// start at the beginning have a FEN header with the position they started from, see fen.rs
+
+use std::error::Error;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use board;
+use board::BoardKind;
+use game::{FenError, GameOutcome, GameState, IllegalMoveReason, RuleSet, Turn};
+use game::{DrawReason, MillRemoval, WinCondition, WinReason};
+use player::PlayerId;
+
+const UNKNOWN: &str = "?";
+const UNFINISHED: &str = "*";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameRecord {
+    pub player1: String,
+    pub player2: String,
+    // year.month.day
+    pub date: String,
+    pub rules: RuleSet,
+    // None if the game hasn't finished
+    pub outcome: Option<GameOutcome>,
+    // Where the game started, if not at the beginning
+    pub fen: Option<String>,
+    pub turns: Vec<Turn>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RecordError {
+    Parse{line: usize, message: String},
+    // turn counts from 1
+    IllegalTurn{turn: usize, reason: IllegalMoveReason},
+}
+
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RecordError::Parse{line, ref message} => write!(f, "Invalid game record on line {}: {}", line, message),
+            RecordError::IllegalTurn{turn, reason} => write!(f, "Illegal turn {}: {}", turn, reason),
+        }
+    }
+}
+
+impl Error for RecordError {}
+
+impl GameRecord {
+    pub fn new(player1: String, player2: String, rules: RuleSet) -> Self {
+        GameRecord { player1, player2, date: today(), rules, outcome: None, fen: None, turns: Vec::new() }
+    }
+
+    pub fn parse(record: &str) -> Result<GameRecord, RecordError> {
+        let mut game_record = GameRecord {
+            player1: UNKNOWN.to_string(),
+            player2: UNKNOWN.to_string(),
+            date: UNKNOWN.to_string(),
+            rules: RuleSet::standard(),
+            outcome: None,
+            fen: None,
+            turns: Vec::new(),
+        };
+        let mut result = UNFINISHED.to_string();
+        let mut termination = None;
+        // Built once the headers have been read, as they say which board it is and who goes first
+        let mut board_and_first_player = None;
+
+        for (i, line) in record.lines().enumerate() {
+            let line_number = i + 1;
+            let error = |message: String| RecordError::Parse { line: line_number, message };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with('[') {
+                let (key, value) = parse_header(line).ok_or_else(|| error(format!("invalid header {}", line)))?;
+                let invalid = || error(format!("invalid {} {}", key, value));
+                let rules = &mut game_record.rules;
+
+                match key {
+                    "Player1" => game_record.player1 = value.to_string(),
+                    "Player2" => game_record.player2 = value.to_string(),
+                    "Date" => game_record.date = value.to_string(),
+                    "Board" => rules.board = parse_board_kind(value).ok_or_else(invalid)?,
+                    "Pieces" => rules.pieces_per_player = value.parse().ok().filter(|p| *p > 0).ok_or_else(invalid)?,
+                    "Flying" => rules.flying = value.parse().map_err(|_| invalid())?,
+                    "MillRemoval" => rules.mill_removal = parse_mill_removal(value).ok_or_else(invalid)?,
+                    "WinCondition" => rules.win_condition = parse_win_condition(value).ok_or_else(invalid)?,
+                    "MoveLimit" => rules.move_limit = match value {
+                        "-" => None,
+                        _ => Some(value.parse().map_err(|_| invalid())?),
+                    },
+                    "RepetitionLimit" => rules.repetition_limit = match value {
+                        "-" => None,
+                        _ => Some(value.parse().map_err(|_| invalid())?),
+                    },
+                    "FEN" => game_record.fen = Some(value.to_string()),
+                    "Result" => result = value.to_string(),
+                    "Termination" => termination = Some(value.to_string()),
+                    _ => (),
+                }
+                continue;
+            }
+
+            if board_and_first_player.is_none() {
+                let start = game_record.start().map_err(|e| error(e.to_string()))?;
+                board_and_first_player = Some((start.board, start.current_player_id));
+            }
+            let (ref board, first_player) = *board_and_first_player.as_ref().unwrap();
+
+            for token in line.split_whitespace() {
+                if token.ends_with('.') || is_result(token) {
+                    continue;
+                }
+                let player_id = game_record.turns.last().map_or(first_player, |t| t.player_id().other());
+                let turn = Turn::from_algebraic(token, player_id, board)
+                    .ok_or_else(|| error(format!("invalid turn {}", token)))?;
+                game_record.turns.push(turn);
+            }
+        }
+
+        game_record.outcome = parse_outcome(&result, termination.as_deref())
+            .map_err(|message| RecordError::Parse { line: 0, message })?;
+
+        Ok(game_record)
+    }
+
+    // Every GameState in the game, starting with the beginning and then one after each turn.
+    // Err at the first turn that couldn't have been made. An unfinished game's last turn can be
+    // missing its removal, if it was saved before the removal was made
+    pub fn replay(&self) -> Result<Vec<GameState>, RecordError> {
+        let mut game_state = self.start().map_err(|e| RecordError::Parse { line: 0, message: e.to_string() })?;
+        let mut states = vec![game_state.clone()];
+
+        for (i, turn) in self.turns.iter().enumerate() {
+            let illegal = |reason| RecordError::IllegalTurn { turn: i + 1, reason };
+            let removal_pending = self.outcome.is_none() && i + 1 == self.turns.len();
+
+            game_state.is_legal(&turn.ply).map_err(illegal)?;
+            game_state = game_state.child(turn.ply);
+
+            match turn.removal {
+                Some(removal) => {
+                    game_state.is_legal(&removal).map_err(illegal)?;
+                    game_state = game_state.child(removal);
+                },
+                None if game_state.can_current_player_mill() && !removal_pending => return Err(illegal(IllegalMoveReason::ExpectedRemoval)),
+                None => (),
+            }
+
+            states.push(game_state.clone());
+        }
+
+        Ok(states)
+    }
+
+    fn start(&self) -> Result<GameState, FenError> {
+        match self.fen {
+            Some(ref fen) => GameState::from_fen(fen, self.rules),
+            None => Ok(GameState::at_beginning(self.rules)),
+        }
+    }
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rules = &self.rules;
+        let win_condition = match rules.win_condition {
+            WinCondition::Score(score) => format!("Score {}", score),
+            win_condition => format!("{:?}", win_condition),
+        };
+        let move_limit = rules.move_limit.map_or("-".to_string(), |limit| limit.to_string());
+        let repetition_limit = rules.repetition_limit.map_or("-".to_string(), |limit| limit.to_string());
+
+        writeln!(f, "[Player1 \"{}\"]", self.player1)?;
+        writeln!(f, "[Player2 \"{}\"]", self.player2)?;
+        writeln!(f, "[Date \"{}\"]", self.date)?;
+        writeln!(f, "[Board \"{:?}\"]", rules.board)?;
+        writeln!(f, "[Pieces \"{}\"]", rules.pieces_per_player)?;
+        writeln!(f, "[Flying \"{}\"]", rules.flying)?;
+        writeln!(f, "[MillRemoval \"{:?}\"]", rules.mill_removal)?;
+        writeln!(f, "[WinCondition \"{}\"]", win_condition)?;
+        writeln!(f, "[MoveLimit \"{}\"]", move_limit)?;
+        writeln!(f, "[RepetitionLimit \"{}\"]", repetition_limit)?;
+        if let Some(ref fen) = self.fen {
+            writeln!(f, "[FEN \"{}\"]", fen)?;
+        }
+        writeln!(f, "[Result \"{}\"]", result(self.outcome))?;
+        if let Some(outcome) = self.outcome {
+            let termination = match outcome {
+                GameOutcome::Win{reason, ..} => format!("{:?}", reason),
+                GameOutcome::Draw{reason} => format!("{:?}", reason),
+            };
+            writeln!(f, "[Termination \"{}\"]", termination)?;
+        }
+        writeln!(f)?;
+
+        let board = board::build(rules.board);
+        let mut move_text: Vec<String> = Vec::new();
+        for (i, turn) in self.turns.iter().enumerate() {
+            if i % 2 == 0 {
+                move_text.push(format!("{}.", i / 2 + 1));
+            }
+            move_text.push(turn.to_algebraic(&board));
+        }
+        move_text.push(result(self.outcome).to_string());
+
+        writeln!(f, "{}", move_text.join(" "))
+    }
+}
+
+fn parse_header(line: &str) -> Option<(&str, &str)> {
+    let line = line.strip_prefix('[')?.strip_suffix(']')?;
+    let quote = line.find('"')?;
+    let key = line[..quote].trim();
+    let value = line[quote..].strip_prefix('"')?.strip_suffix('"')?;
+    Some((key, value))
+}
+
+fn parse_board_kind(value: &str) -> Option<BoardKind> {
+    [BoardKind::NineMen, BoardKind::TwelveMen, BoardKind::SixMen, BoardKind::ThreeMen].iter()
+        .find(|kind| format!("{:?}", kind) == value)
+        .cloned()
+}
+
+fn parse_mill_removal(value: &str) -> Option<MillRemoval> {
+    [MillRemoval::FromMillsIfNoOther, MillRemoval::NotFromMills, MillRemoval::AnyPiece].iter()
+        .find(|removal| format!("{:?}", removal) == value)
+        .cloned()
+}
+
+fn parse_win_condition(value: &str) -> Option<WinCondition> {
+    match value {
+        "TooFewPieces" => Some(WinCondition::TooFewPieces),
+        "Mill" => Some(WinCondition::Mill),
+        _ => value.strip_prefix("Score ")?.parse().ok().map(WinCondition::Score),
+    }
+}
+
+fn result(outcome: Option<GameOutcome>) -> &'static str {
+    match outcome {
+        Some(GameOutcome::Win{player: PlayerId::One, ..}) => "1-0",
+        Some(GameOutcome::Win{player: PlayerId::Two, ..}) => "0-1",
+        Some(GameOutcome::Draw{..}) => "1/2-1/2",
+        None => UNFINISHED,
+    }
+}
+
+fn is_result(token: &str) -> bool {
+    ["1-0", "0-1", "1/2-1/2", UNFINISHED].contains(&token)
+}
+
+fn parse_outcome(result: &str, termination: Option<&str>) -> Result<Option<GameOutcome>, String> {
+    let win_reasons = [WinReason::NoMoves, WinReason::TooFewPieces, WinReason::ScoreReached,
+                       WinReason::Resignation, WinReason::IllegalMoves];
+    let draw_reasons = [DrawReason::Repetition, DrawReason::MoveLimit];
+
+    let win_reason = || win_reasons.iter()
+        .find(|reason| Some(format!("{:?}", reason).as_str()) == termination)
+        .cloned()
+        .ok_or(format!("invalid Termination {:?} for a win", termination));
+
+    match result {
+        UNFINISHED => Ok(None),
+        "1-0" => Ok(Some(GameOutcome::Win { player: PlayerId::One, reason: win_reason()? })),
+        "0-1" => Ok(Some(GameOutcome::Win { player: PlayerId::Two, reason: win_reason()? })),
+        "1/2-1/2" => draw_reasons.iter()
+            .find(|reason| Some(format!("{:?}", reason).as_str()) == termination)
+            .map(|reason| Some(GameOutcome::Draw { reason: *reason }))
+            .ok_or(format!("invalid Termination {:?} for a draw", termination)),
+        _ => Err(format!("invalid Result {}", result)),
+    }
+}
+
+// year.month.day in UTC, see: http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn today() -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let days = (seconds / 86_400) as i64 + 719_468;
+
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{}.{:02}.{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::Ply;
+    use game::game_state::last_turns;
+
+    #[test]
+    fn round_trips_and_replays() {
+        let rules = RuleSet::standard().with_flying(true).with_repetition_limit(Some(5));
+        let mut game_state = GameState::at_beginning(rules);
+        let mut record = GameRecord::new("A".to_string(), "B \"the bot\"".to_string(), rules);
+
+        record.turns = last_turns(rules, 30);
+        for turn in &record.turns {
+            game_state.apply_turn(turn);
+        }
+        record.outcome = Some(GameOutcome::Draw { reason: DrawReason::MoveLimit });
+
+        let parsed = GameRecord::parse(&record.to_string()).unwrap();
+        assert_eq!(parsed, record);
+        assert!(record.turns.iter().any(|t| t.removal.is_some()));
+        assert!(record.turns.iter().any(|t| matches!(t.ply, Ply::Move{..})));
+
+        let states = parsed.replay().unwrap();
+        assert_eq!(states.len(), record.turns.len() + 1);
+        assert_eq!(*states.last().unwrap(), game_state);
+    }
+
+    #[test]
+    fn records_can_start_mid_game() {
+        let rules = RuleSet::standard();
+        let fen = "1......./......../........ 2 8/9 - 0/0 0";
+        let mut game_state = GameState::from_fen(fen, rules).unwrap();
+        let mut record = GameRecord::new("A".to_string(), "B".to_string(), rules);
+        record.fen = Some(fen.to_string());
+
+        for _ in 0..3 {
+            let turn = game_state.available_turns().pop().unwrap();
+            game_state.apply_turn(&turn);
+            record.turns.push(turn);
+        }
+        assert_eq!(record.turns[0].player_id(), PlayerId::Two);
+
+        let parsed = GameRecord::parse(&record.to_string()).unwrap();
+        assert_eq!(parsed, record);
+        assert_eq!(*parsed.replay().unwrap().last().unwrap(), game_state);
+    }
+
+    #[test]
+    fn reports_bad_records() {
+        let header = "[Result \"*\"]\n\n";
+        assert_eq!(GameRecord::parse(&format!("{}1. d7 z9", header)),
+                   Err(RecordError::Parse { line: 3, message: "invalid turn z9".to_string() }));
+        assert_eq!(GameRecord::parse(&format!("{}1. d7 d7", header)).unwrap().replay(),
+                   Err(RecordError::IllegalTurn { turn: 2, reason: IllegalMoveReason::PointOccupied }));
+    }
+
+    #[test]
+    fn unfinished_games_can_stop_before_a_removal() {
+        // Player 1 has made a mill on the top row and hasn't taken anything yet
+        let record = GameRecord::parse("[Result \"*\"]\n\n1. a7 b6 2. d7 d6 3. g7 *").unwrap();
+        let last = record.replay().unwrap().pop().unwrap();
+        assert!(last.can_current_player_mill());
+
+        let finished = GameRecord { outcome: Some(GameOutcome::Draw { reason: DrawReason::MoveLimit }), ..record };
+        assert_eq!(finished.replay(), Err(RecordError::IllegalTurn { turn: 5, reason: IllegalMoveReason::ExpectedRemoval }));
+    }
+}
@@ -0,0 +1,111 @@
+// The rules a game is played with, so that variants don't need their own copy of the crate.
+// Defaults are standard Nine Men's Morris (without flying).
+
+use board::BoardKind;
+
+use self::MillRemoval::*;
+use self::WinCondition::*;
+
+pub const STANDARD_PIECES: i8 = 9;
+pub const TWELVE_MENS_PIECES: i8 = 12;
+pub const SIX_MENS_PIECES: i8 = 6;
+pub const THREE_MENS_PIECES: i8 = 3;
+// Moves, by either player, without a mill before the game is a draw
+pub const MOVE_LIMIT: u16 = 50;
+// Times the same position, with the same player to move, can come up before the game is a draw
+pub const REPETITION_LIMIT: u8 = 3;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct RuleSet {
+    pub board: BoardKind,
+    pub pieces_per_player: i8,
+    pub flying: bool,
+    pub mill_removal: MillRemoval,
+    pub win_condition: WinCondition,
+    // None to play on for as long as it takes
+    pub move_limit: Option<u16>,
+    // None to allow any number of repetitions. Only Game counts them, GameState doesn't know
+    pub repetition_limit: Option<u8>,
+}
+
+// Which opponent pieces can be removed after making a mill.
+// The standard rule is FromMillsIfNoOther: pieces in mills are safe unless every piece is in one.
+// With NotFromMills, a mill that has nothing it can take doesn't count and the turn passes
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum MillRemoval {
+    FromMillsIfNoOther,
+    NotFromMills,
+    AnyPiece,
+}
+
+// Having no moves left always loses; this is how else a game can be won.
+// Score (first to make that many mills) is the legacy rule and only works for nine pieces at 7.
+// Mill is for Three Men's Morris, where the first mill wins and no pieces are removed
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum WinCondition {
+    TooFewPieces,
+    Score(i8),
+    Mill,
+}
+
+impl RuleSet {
+    pub fn standard() -> Self {
+        RuleSet {
+            board: BoardKind::NineMen,
+            pieces_per_player: STANDARD_PIECES,
+            flying: false,
+            mill_removal: FromMillsIfNoOther,
+            win_condition: TooFewPieces,
+            move_limit: Some(MOVE_LIMIT),
+            repetition_limit: Some(REPETITION_LIMIT),
+        }
+    }
+
+    pub fn twelve_mens_morris() -> Self {
+        RuleSet {
+            board: BoardKind::TwelveMen,
+            pieces_per_player: TWELVE_MENS_PIECES,
+            ..RuleSet::standard()
+        }
+    }
+
+    pub fn six_mens_morris() -> Self {
+        RuleSet {
+            board: BoardKind::SixMen,
+            pieces_per_player: SIX_MENS_PIECES,
+            ..RuleSet::standard()
+        }
+    }
+
+    pub fn three_mens_morris() -> Self {
+        RuleSet {
+            board: BoardKind::ThreeMen,
+            pieces_per_player: THREE_MENS_PIECES,
+            win_condition: Mill,
+            ..RuleSet::standard()
+        }
+    }
+
+    // How games were won before pieces on the board were tracked
+    pub fn legacy() -> Self {
+        RuleSet { win_condition: Score(STANDARD_PIECES - 2), ..RuleSet::standard() }
+    }
+
+    pub fn with_flying(self, flying: bool) -> Self {
+        RuleSet { flying, ..self }
+    }
+
+    pub fn with_move_limit(self, move_limit: Option<u16>) -> Self {
+        RuleSet { move_limit, ..self }
+    }
+
+    pub fn with_repetition_limit(self, repetition_limit: Option<u8>) -> Self {
+        RuleSet { repetition_limit, ..self }
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet::standard()
+    }
+}
@@ -0,0 +1,101 @@
+// A whole turn as a single action: a placement or move, plus the piece it removes if it makes a mill.
+// Games are still played one Ply at a time, Turns are for engines and notation that want one move per turn.
+// A game that starts waiting for a removal has a first turn that's just the Mill, as ply
+
+use board::Board;
+use game::Ply;
+use player::PlayerId;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Turn {
+    pub ply: Ply,
+    // Always a Ply::Mill
+    pub removal: Option<Ply>,
+}
+
+impl Turn {
+    pub fn new(ply: Ply, removal: Option<Ply>) -> Self {
+        assert!(removal.iter().all(|r| r.is_mill()), "Removal must be a Ply::Mill, found {:?}", removal);
+        Turn { ply, removal }
+    }
+
+    pub fn player_id(&self) -> PlayerId {
+        self.ply.player_id()
+    }
+
+    pub fn plies(&self) -> Vec<Ply> {
+        let mut plies = vec![self.ply];
+        plies.extend(self.removal);
+        plies
+    }
+
+    // Algebraic notation, with the removal straight after the ply, e.g. "b2xd7" or "a1-a4xd7"
+    pub fn to_algebraic(&self, board: &Board) -> String {
+        self.plies().iter().map(|p| p.to_algebraic(board)).collect()
+    }
+
+    // Parse algebraic notation as written by to_algebraic. None if it doesn't name points on board
+    pub fn from_algebraic(notation: &str, player_id: PlayerId, board: &Board) -> Option<Turn> {
+        let parts: Vec<&str> = notation.trim().split('x').collect();
+        if parts.len() == 2 && parts[0].is_empty() {
+            let removal = Ply::Mill { player_id, piece_id: 0 }.from_algebraic(parts[1], board)?;
+            return Some(Turn::new(removal, None));
+        }
+
+        let kind = if parts[0].contains('-') {
+            Ply::Move { player_id, mv: (0, 0) }
+        } else {
+            Ply::Placement { player_id, piece_id: 0 }
+        };
+        let ply = kind.from_algebraic(parts[0], board)?;
+
+        let removal = match parts.len() {
+            1 => None,
+            2 => Some(Ply::Mill { player_id, piece_id: 0 }.from_algebraic(parts[1], board)?),
+            _ => return None,
+        };
+
+        Some(Turn::new(ply, removal))
+    }
+
+    // Removal is written after an x, e.g. "0n,0ex1s"
+    pub fn to_notation(&self, board: &Board) -> String {
+        match self.removal {
+            Some(removal) => format!("{}x{}", self.ply.to_notation(board), removal.to_notation(board)),
+            None => self.ply.to_notation(board),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use board::{build, BoardKind};
+    use player::PlayerId::One;
+
+    #[test]
+    fn algebraic_round_trips() {
+        let board = build(BoardKind::NineMen);
+        let point = |id: &str| board.topology().index_of(id).unwrap();
+        let mill = Ply::Mill { player_id: One, piece_id: point("0n") };
+        let removal = Some(mill);
+
+        let turns = [
+            Turn::new(Ply::Placement { player_id: One, piece_id: point("2sw") }, None),
+            Turn::new(Ply::Placement { player_id: One, piece_id: point("2sw") }, removal),
+            Turn::new(Ply::Move { player_id: One, mv: (point("0sw"), point("0w")) }, removal),
+            Turn::new(mill, None),
+        ];
+        for turn in &turns {
+            let notation = turn.to_algebraic(&board);
+            assert_eq!(Turn::from_algebraic(&notation, One, &board), Some(*turn), "{}", notation);
+        }
+        assert_eq!(turns[1].to_algebraic(&board), "c3xd7");
+
+        assert_eq!(turns[3].to_algebraic(&board), "xd7");
+
+        for invalid in &["xd4", "xxd7", "c3xd7xd7", "c3-", "c3xd4", "c3x"] {
+            assert_eq!(Turn::from_algebraic(invalid, One, &board), None, "{}", invalid);
+        }
+    }
+}
@@ -2,9 +2,11 @@ extern crate itertools;
 extern crate term_painter;
 extern crate rand;
 
+pub mod error;
 pub mod board;
 pub mod player;
 pub mod game;
+pub mod zobrist;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,156 @@
+// A bot that looks depth plies ahead with minimax and alpha-beta pruning, scoring the positions it
+// can't see past with an evaluation function. Removals are plies too, so a player can have two
+// plies in a row. Repetitions aren't known to GameState, so aren't looked for
+
+use game::{GameState, Ply};
+use player::InputHandler;
+use player::PlayerId;
+
+pub const DEFAULT_DEPTH: u8 = 4;
+
+// Better than any evaluation, so wins are always taken and losses avoided
+const WIN: i32 = 1_000_000;
+const PIECE_VALUE: i32 = 100;
+
+// How good game_state is for player_id, higher is better. Only used for positions that haven't
+// finished
+pub type Evaluation = fn(&GameState, PlayerId) -> i32;
+
+pub struct AlphaBeta {
+    player_id: PlayerId,
+    depth: u8,
+    evaluate: Evaluation,
+    game_state: Option<GameState>,
+}
+
+impl AlphaBeta {
+    pub fn new(depth: u8) -> Self {
+        AlphaBeta::with_evaluation(depth, evaluate)
+    }
+
+    pub fn with_evaluation(depth: u8, evaluate: Evaluation) -> Self {
+        assert!(depth > 0, "AlphaBeta has to look at least one ply ahead");
+        AlphaBeta { player_id: PlayerId::One, depth, evaluate, game_state: None }
+    }
+
+    // The available ply with the best score, the first if there's a tie
+    fn choose(&self, available_plies: Vec<Ply>) -> Ply {
+        let game_state = self.game_state.as_ref().expect("AlphaBeta has not been given a GameState");
+
+        let mut alpha = -WIN - i32::from(self.depth);
+        let mut best = None;
+        for ply in available_plies {
+            let value = match game_state.try_child(ply) {
+                Ok(child) => self.search(&child, self.depth - 1, alpha, WIN + i32::from(self.depth)),
+                Err(_) => continue,
+            };
+            if best.is_none() || value > alpha {
+                alpha = value;
+                best = Some(ply);
+            }
+        }
+        best.expect("AlphaBeta has no legal plies to choose from")
+    }
+
+    fn search(&self, game_state: &GameState, depth: u8, mut alpha: i32, mut beta: i32) -> i32 {
+        // Sooner wins, and later losses, are better, which depth left says
+        if let Some(outcome) = game_state.outcome() {
+            return match outcome.winner() {
+                Some(winner) if winner == self.player_id => WIN + i32::from(depth),
+                Some(_) => -WIN - i32::from(depth),
+                None => 0,
+            };
+        }
+
+        let children = game_state.children();
+        if depth == 0 || children.is_empty() {
+            return (self.evaluate)(game_state, self.player_id);
+        }
+
+        let maximising = game_state.current_player_id == self.player_id;
+        for child in &children {
+            let value = self.search(child, depth - 1, alpha, beta);
+            if maximising {
+                alpha = alpha.max(value);
+            } else {
+                beta = beta.min(value);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+        if maximising { alpha } else { beta }
+    }
+}
+
+// Pieces, counting a removal still to be made as made, then how many moves each player has
+pub fn evaluate(game_state: &GameState, player_id: PlayerId) -> i32 {
+    let value_for = |player_id: PlayerId| {
+        let pieces = i32::from(game_state.player_state(player_id).total_pieces());
+        let removal = game_state.current_player_id == player_id && game_state.can_current_player_mill();
+        let moves = game_state.available_moves(player_id).len() as i32;
+        PIECE_VALUE * (pieces + i32::from(removal)) + moves
+    };
+    value_for(player_id) - value_for(player_id.other())
+}
+
+impl InputHandler for AlphaBeta {
+    fn give_new_game_state(&mut self, game_state: GameState) {
+        self.game_state = Some(game_state);
+    }
+
+    fn get_placement(&mut self, available_places: Vec<Ply>) -> Ply {
+        self.choose(available_places)
+    }
+
+    fn get_move(&mut self, available_moves: Vec<Ply>) -> Ply {
+        self.choose(available_moves)
+    }
+
+    fn get_mill(&mut self, available_mills: Vec<Ply>) -> Ply {
+        self.choose(available_mills)
+    }
+
+    fn to_string(&self) -> String {
+        format!("AlphaBeta InputHandler (depth {})", self.depth)
+    }
+
+    fn set_player_id(&mut self, player_id: PlayerId) {
+        self.player_id = player_id;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::RuleSet;
+
+    fn choose(fen: &str, depth: u8) -> Ply {
+        let game_state = GameState::from_fen(fen, RuleSet::three_mens_morris()).unwrap();
+        let mut alpha_beta = AlphaBeta::new(depth);
+        alpha_beta.set_player_id(game_state.current_player_id);
+        let available_places = game_state.available_plies();
+        alpha_beta.give_new_game_state(game_state);
+        alpha_beta.get_placement(available_places)
+    }
+
+    #[test]
+    fn takes_wins_and_blocks_losses() {
+        // 0nw, 0n and 0ne are a mill
+        assert_eq!(choose("11..22../. 1 1/1 - 0/0 0", 1), Ply::Placement { player_id: PlayerId::One, piece_id: 2 });
+        assert_eq!(choose("11..2.../. 2 1/2 - 0/0 0", 2), Ply::Placement { player_id: PlayerId::Two, piece_id: 2 });
+    }
+
+    #[test]
+    fn evaluation_is_pluggable() {
+        // Only cares about having a piece on 0sw
+        fn likes_0sw(game_state: &GameState, player_id: PlayerId) -> i32 {
+            if game_state.board.piece_at(6).owned_by(player_id) { 1 } else { 0 }
+        }
+        let game_state = GameState::from_fen(".1..2.../. 1 2/2 - 0/0 0", RuleSet::three_mens_morris()).unwrap();
+        let mut alpha_beta = AlphaBeta::with_evaluation(1, likes_0sw);
+        alpha_beta.give_new_game_state(game_state.clone());
+        let ply = alpha_beta.get_placement(game_state.available_plies());
+        assert_eq!(ply, Ply::Placement { player_id: PlayerId::One, piece_id: 6 });
+    }
+}
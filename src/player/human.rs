@@ -1,16 +1,27 @@
 use std::io;
-use itertools::Itertools;
+use std::mem;
 
+use board::Board;
 use game::GameState;
 use game::Ply;
 use game::Ply::*;
+use game::Turn;
 use player::InputHandler;
+use player::PlayerId;
 
 pub struct Human {
-    pub player_id: i8,
+    pub player_id: PlayerId,
+    // Needed to turn what's typed in into plies
+    game_state: Option<GameState>,
+    // Typed in along with a placement or move, e.g. "b2xd7", and made when the mill is asked for
+    removal: Option<Ply>,
 }
 
 impl Human {
+    pub fn new(player_id: PlayerId) -> Self {
+        Human { player_id, game_state: None, removal: None }
+    }
+
     fn get_input(&self) -> String {
         let mut input = String::new();
         io::stdin().read_line(&mut input)
@@ -18,72 +29,88 @@ impl Human {
         input.trim().to_string().to_lowercase()
     }
 
-    fn placement_ply(&self, piece_id: String) -> Ply {
-        Placement { player_id: self.player_id, piece_id: piece_id }
+    fn game_state(&self) -> &GameState {
+        self.game_state.as_ref().expect("Human has not been given a GameState")
     }
 
-    fn move_ply(&self, mv: (String, String)) -> Ply {
-        Move { player_id: self.player_id, mv: mv }
+    fn board(&self) -> &Board {
+        &self.game_state().board
     }
 
-    fn mill_ply(&self, piece_id: String) -> Ply {
-        Mill { player_id: self.player_id, piece_id: piece_id }
+    fn notation(&self, plies: &[Ply]) -> Vec<String> {
+        plies.iter().map(|p| p.to_algebraic(self.board())).collect()
     }
-}
 
-impl InputHandler for Human {
-    fn give_new_game_state(&mut self, _game_state: GameState) {
-        // Don't need to do anything
+    // Placements and moves can have their removal typed in straight after, as for Turn
+    fn parse(&self, kind: Ply, input: &str) -> Option<Turn> {
+        let turn = match kind {
+            Mill{..} => kind.from_algebraic(input, self.board()).map(|ply| Turn::new(ply, None)),
+            _ => Turn::from_algebraic(input, self.player_id, self.board())
+                .filter(|turn| mem::discriminant(&turn.ply) == mem::discriminant(&kind)),
+        };
+        turn.or_else(|| kind.from_notation(input, self.board()).map(|ply| Turn::new(ply, None)))
     }
 
-    fn get_placement(&mut self, available_places: Vec<Ply>) -> Ply {
-        println!("Can place at: {:?}", available_places);
+    // A removal typed in early has to be one the ply makes a mill for
+    fn removal_allowed(&self, turn: &Turn) -> bool {
+        match turn.removal {
+            Some(removal) => {
+                let mut game_state = self.game_state().clone();
+                game_state.apply(&turn.ply);
+                game_state.can_current_player_mill() && game_state.available_mills().contains(&removal)
+            },
+            None => true,
+        }
+    }
+
+    // Keep asking until what's typed in is one of available_plies. Either algebraic notation or
+    // position ids can be used
+    fn choose(&mut self, kind: Ply, available_plies: &[Ply], invalid_message: &str) -> Ply {
         loop {
             let input = self.get_input();
-            let placement = self.placement_ply(input);
-            if available_places.contains(&placement) {
-                break placement
+            match self.parse(kind, &input) {
+                Some(turn) if available_plies.contains(&turn.ply) && self.removal_allowed(&turn) => {
+                    self.removal = turn.removal;
+                    break turn.ply
+                },
+                _ => println!("{}", invalid_message),
             }
-            println!("Invalid placement");
         }
     }
+}
+
+impl InputHandler for Human {
+    fn give_new_game_state(&mut self, game_state: GameState) {
+        self.game_state = Some(game_state);
+    }
+
+    fn get_placement(&mut self, available_places: Vec<Ply>) -> Ply {
+        println!("Can place at: {:?}", self.notation(&available_places));
+        let kind = Placement { player_id: self.player_id, piece_id: 0 };
+        self.choose(kind, &available_places, "Invalid placement")
+    }
 
     fn get_move(&mut self, available_moves: Vec<Ply>) -> Ply {
-        loop {
-            println!("Available moves: {:?}", available_moves);
-            match self.get_input()
-                .split(",")
-                .map(|m| m.trim().to_string())
-                .next_tuple() {
-                Some(input) => {
-                    let mv = self.move_ply(input);
-                    if available_moves.contains(&mv) {
-                        break mv
-                    }
-                    println!("Invalid move");
-                }
-                None => println!("Invalid move, must be in format 0n,0e"),
-            }
-        }
+        println!("Available moves: {:?}", self.notation(&available_moves));
+        let kind = Move { player_id: self.player_id, mv: (0, 0) };
+        self.choose(kind, &available_moves, "Invalid move, must be one of the available moves in format a1-a4")
     }
 
     fn get_mill(&mut self, available_mills: Vec<Ply>) -> Ply {
-        println!("Mill! Select piece to destroy: {:?}", available_mills);
-        loop {
-            let input = self.get_input();
-            let mill = self.mill_ply(input);
-            if available_mills.contains(&mill) {
-                break mill
-            }
-            println!("Invalid mill");
+        if let Some(removal) = self.removal.take().filter(|r| available_mills.contains(r)) {
+            return removal;
         }
+
+        println!("Mill! Select piece to destroy: {:?}", self.notation(&available_mills));
+        let kind = Mill { player_id: self.player_id, piece_id: 0 };
+        self.choose(kind, &available_mills, "Invalid mill")
     }
 
     fn to_string(&self) -> String {
         "Human InputHandler".to_string()
     }
 
-    fn set_player_id(&mut self, player_id: i8) {
+    fn set_player_id(&mut self, player_id: PlayerId) {
         self.player_id = player_id;
     }
 }
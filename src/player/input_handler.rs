@@ -1,5 +1,6 @@
 use game::GameState;
 use game::Ply;
+use player::PlayerId;
 
 pub trait InputHandler {
     fn give_new_game_state(&mut self, game_state: GameState);
@@ -7,5 +8,13 @@ pub trait InputHandler {
     fn get_move(&mut self, available_moves: Vec<Ply>) -> Ply;
     fn get_mill(&mut self, available_mills: Vec<Ply>) -> Ply;
     fn to_string(&self) -> String;
-    fn set_player_id(&mut self, player_id: i8);
+    fn set_player_id(&mut self, player_id: PlayerId);
+
+    // Every ply in the game so far, given along with each new game state
+    fn give_plies(&mut self, _plies: Vec<Ply>) {}
+
+    // Asked at the start of each turn
+    fn resigns(&mut self) -> bool {
+        false
+    }
 }
@@ -1,27 +1,31 @@
+mod alpha_beta;
 mod input_handler;
 mod human;
 mod random;
 mod player_state;
+mod player_id;
 
 use std::fmt;
 
+pub use self::alpha_beta::{AlphaBeta, Evaluation, evaluate, DEFAULT_DEPTH};
 pub use self::input_handler::InputHandler;
 pub use self::human::Human;
 pub use self::random::Random;
-pub use self::player_state::PlayerState;
+pub use self::player_state::{PlayerState, MINIMUM_PIECES};
+pub use self::player_id::PlayerId;
 
 use game::GameState;
 use game::Ply;
 
 pub struct Player {
     pub name: String,
-    pub id: i8,
-    input_handler: Box<InputHandler>,
+    pub id: PlayerId,
+    input_handler: Box<dyn InputHandler>,
 }
 
 impl Player {
-    pub fn new(name: String, id: i8, input_handler: Box<InputHandler>) -> Self {
-        Player { name: name, id: id, input_handler: input_handler }
+    pub fn new(name: String, id: PlayerId, input_handler: Box<dyn InputHandler>) -> Self {
+        Player { name, id, input_handler }
     }
 
     pub fn mill(&mut self, available_mills: Vec<Ply>) -> Ply {
@@ -32,7 +36,11 @@ impl Player {
         self.input_handler.give_new_game_state(game_state);
     }
 
-    pub fn set_input_handler_player_id(&mut self, player_id: i8) {
+    pub fn give_plies(&mut self, plies: Vec<Ply>) {
+        self.input_handler.give_plies(plies);
+    }
+
+    pub fn set_input_handler_player_id(&mut self, player_id: PlayerId) {
         self.input_handler.set_player_id(player_id);
     }
 
@@ -43,6 +51,10 @@ impl Player {
     pub fn get_placement(&mut self, available_places: Vec<Ply>) -> Ply {
         self.input_handler.get_placement(available_places)
     }
+
+    pub fn resigns(&mut self) -> bool {
+        self.input_handler.resigns()
+    }
 }
 
 impl fmt::Debug for Player {
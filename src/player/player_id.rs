@@ -0,0 +1,53 @@
+// Which of the two players. Numbered 1 and 2 when shown to people
+
+use std::fmt;
+
+use self::PlayerId::*;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum PlayerId {
+    One,
+    Two,
+}
+
+impl PlayerId {
+    pub fn both() -> [PlayerId; 2] {
+        [One, Two]
+    }
+
+    pub fn other(self) -> PlayerId {
+        match self {
+            One => Two,
+            Two => One,
+        }
+    }
+
+    pub fn number(self) -> i8 {
+        match self {
+            One => 1,
+            Two => 2,
+        }
+    }
+
+    pub fn from_number(number: i8) -> Option<PlayerId> {
+        match number {
+            1 => Some(One),
+            2 => Some(Two),
+            _ => None,
+        }
+    }
+
+    // For arrays with an entry per player
+    pub fn index(self) -> usize {
+        match self {
+            One => 0,
+            Two => 1,
+        }
+    }
+}
+
+impl fmt::Display for PlayerId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
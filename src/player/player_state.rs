@@ -1,46 +1,99 @@
 use std::fmt;
 
+use error::NinemanError;
 use game::Ply;
+use game::RuleSet;
+use game::WinReason;
+use game::WinCondition::*;
 
-pub const WIN_SCORE: i8 = 7;
 pub const STARTING_SCORE: i8 = 0;
-pub const STARTING_PIECES: i8 = 9;
+pub const FLYING_PIECES: i8 = 3;
+// A player with fewer pieces than this left can't make a mill, so has lost
+pub const MINIMUM_PIECES: i8 = 3;
 
 #[derive(Clone, Eq, PartialEq)]
 pub struct PlayerState {
     score: i8,
     pieces_left_to_place: i8,
+    pieces_on_board: i8,
 }
 
 impl PlayerState {
-    pub fn at_beginning() -> Self {
+    pub fn at_beginning(rules: &RuleSet) -> Self {
         PlayerState {
             score: STARTING_SCORE,
-            pieces_left_to_place: STARTING_PIECES,
+            pieces_left_to_place: rules.pieces_per_player,
+            pieces_on_board: 0,
         }
     }
 
+    // For positions part way through a game, e.g. from GameState::from_fen
+    pub fn new(score: i8, pieces_left_to_place: i8, pieces_on_board: i8) -> Self {
+        PlayerState { score, pieces_left_to_place, pieces_on_board }
+    }
+
     pub fn is_placement(&self) -> bool {
         self.pieces_left_to_place > 0
     }
 
+    // Flying is only possible once placement is over, and only if the game allows it
+    pub fn is_flying(&self, flying_allowed: bool) -> bool {
+        flying_allowed && !self.is_placement() && self.pieces_on_board == FLYING_PIECES
+    }
+
     pub fn place_piece(&self) -> PlayerState{
+        self.try_place_piece().expect("Tried to place piece when it was not placement!")
+    }
+
+    pub fn try_place_piece(&self) -> Result<PlayerState, NinemanError> {
         if self.is_placement() {
-            PlayerState { score: self.score.clone(), pieces_left_to_place: self.pieces_left_to_place - 1 }
+            Ok(PlayerState { pieces_left_to_place: self.pieces_left_to_place - 1,
+                             pieces_on_board: self.pieces_on_board + 1, ..self.clone() })
+        } else {
+            Err(NinemanError::NoPiecesToPlace)
+        }
+    }
+
+    pub fn lose_piece(&self) -> PlayerState {
+        self.try_lose_piece().expect("Tried to lose a piece when there are none on the board!")
+    }
+
+    pub fn try_lose_piece(&self) -> Result<PlayerState, NinemanError> {
+        if self.pieces_on_board > 0 {
+            Ok(PlayerState { pieces_on_board: self.pieces_on_board - 1, ..self.clone() })
         } else {
-            panic!("Tried to place piece when it was not placement!");
+            Err(NinemanError::NoPiecesToLose)
         }
     }
 
     pub fn increment_score(&self) -> PlayerState {
-        PlayerState { score: self.score + 1, pieces_left_to_place: self.pieces_left_to_place.clone() }
+        PlayerState { score: self.score + 1, ..self.clone() }
+    }
+
+    pub fn has_won(&self, rules: &RuleSet, other_player_available_moves: Vec<Ply>,
+                    other_player_state: &PlayerState) -> bool {
+        self.win_reason(rules, other_player_available_moves, other_player_state).is_some()
     }
 
-    pub fn has_won(&self, other_player_available_moves: Vec<Ply>,
-                    other_player_placement: bool) -> bool {
+    pub fn win_reason(&self, rules: &RuleSet, other_player_available_moves: Vec<Ply>,
+                    other_player_state: &PlayerState) -> Option<WinReason> {
 
-        self.score >= WIN_SCORE ||
-        (!other_player_placement && other_player_available_moves.is_empty())
+        let won_by_condition = match rules.win_condition {
+            TooFewPieces => other_player_state.total_pieces() < MINIMUM_PIECES,
+            Score(win_score) => self.score >= win_score,
+            Mill => self.score > 0,
+        };
+
+        if won_by_condition {
+            match rules.win_condition {
+                TooFewPieces => Some(WinReason::TooFewPieces),
+                Score(_) | Mill => Some(WinReason::ScoreReached),
+            }
+        } else if !other_player_state.is_placement() && other_player_available_moves.is_empty() {
+            Some(WinReason::NoMoves)
+        } else {
+            None
+        }
     }
 
     pub fn score(&self) -> i8 {
@@ -50,13 +103,21 @@ impl PlayerState {
     pub fn pieces_left_to_place(&self) -> i8 {
         self.pieces_left_to_place
     }
+
+    pub fn pieces_on_board(&self) -> i8 {
+        self.pieces_on_board
+    }
+
+    // Pieces still in play, whether on the board or waiting to be placed
+    pub fn total_pieces(&self) -> i8 {
+        self.pieces_on_board + self.pieces_left_to_place
+    }
 }
 
 impl fmt::Debug for PlayerState {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 
-        return write!(f, "PlayerState (score: {}, pieces_left_to_place: {})",
-                        self.score, self.pieces_left_to_place);
-
+        write!(f, "PlayerState (score: {}, pieces_left_to_place: {}, pieces_on_board: {})",
+                self.score, self.pieces_left_to_place, self.pieces_on_board)
     }
 }
@@ -3,6 +3,7 @@ use rand::{thread_rng, Rng};
 use game::GameState;
 use game::Ply;
 use player::InputHandler;
+use player::PlayerId;
 
 pub struct Random {}
 
@@ -14,21 +15,21 @@ impl InputHandler for Random {
     fn get_placement(&mut self, available_places: Vec<Ply>) -> Ply {
 
         match thread_rng().choose(&available_places) {
-            Some(ply) => ply.to_owned(),
+            Some(ply) => *ply,
             None => panic!("In placement phase and no places to choose from")
         }
     }
 
     fn get_move(&mut self, available_moves: Vec<Ply>) -> Ply {
         match thread_rng().choose(&available_moves) {
-            Some(ply) => ply.to_owned(),
+            Some(ply) => *ply,
             None => panic!("In move phase and no moves to choose from")
         }
     }
 
     fn get_mill(&mut self, available_mills: Vec<Ply>) -> Ply {
         match thread_rng().choose(&available_mills) {
-            Some(ply) => ply.to_owned(),
+            Some(ply) => *ply,
             None => panic!("In mill phase and no mills to choose from")
         }
     }
@@ -37,7 +38,7 @@ impl InputHandler for Random {
         "Random InputHandler".to_string()
     }
 
-    fn set_player_id(&mut self, _player_id: i8) {
+    fn set_player_id(&mut self, _player_id: PlayerId) {
         // Don't need to do anything
     }
 }
@@ -0,0 +1,90 @@
+// Random keys for Zobrist hashing of positions, see: https://en.wikipedia.org/wiki/Zobrist_hashing
+// Generated at compile time from a fixed seed, so hashes are the same on every run and machine.
+// Board keeps the hash of its pieces up to date as they're placed and removed, and GameState
+// adds in whose turn it is, pieces in hand and whether a removal is pending.
+
+use player::PlayerId;
+
+// Most points a board can have and still be hashed
+pub const MAX_POINTS: usize = 64;
+// Pieces in hand can be any i8 count
+const MAX_HAND: usize = 128;
+
+const SEED: u64 = 0x6e69_6e65_6d61_6e21;
+
+struct Keys {
+    pieces: [[u64; 2]; MAX_POINTS],
+    hands: [[u64; MAX_HAND]; 2],
+    player2_to_move: u64,
+    pending_removal: u64,
+}
+
+static KEYS: Keys = generate();
+
+// See: https://en.wikipedia.org/wiki/Xorshift#splitmix64
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (state, z ^ (z >> 31))
+}
+
+const fn generate() -> Keys {
+    let mut keys = Keys {
+        pieces: [[0; 2]; MAX_POINTS],
+        hands: [[0; MAX_HAND]; 2],
+        player2_to_move: 0,
+        pending_removal: 0,
+    };
+    let mut state = SEED;
+    let mut key;
+
+    let mut point = 0;
+    while point < MAX_POINTS {
+        let mut player = 0;
+        while player < 2 {
+            (state, key) = splitmix64(state);
+            keys.pieces[point][player] = key;
+            player += 1;
+        }
+        point += 1;
+    }
+
+    let mut player = 0;
+    while player < 2 {
+        let mut count = 0;
+        while count < MAX_HAND {
+            (state, key) = splitmix64(state);
+            keys.hands[player][count] = key;
+            count += 1;
+        }
+        player += 1;
+    }
+
+    (state, key) = splitmix64(state);
+    keys.player2_to_move = key;
+    (_, key) = splitmix64(state);
+    keys.pending_removal = key;
+
+    keys
+}
+
+pub fn piece(point: usize, player_id: PlayerId) -> u64 {
+    KEYS.pieces[point][player_id.index()]
+}
+
+pub fn hand(player_id: PlayerId, pieces_left_to_place: i8) -> u64 {
+    KEYS.hands[player_id.index()][pieces_left_to_place.max(0) as usize]
+}
+
+pub fn side_to_move(player_id: PlayerId) -> u64 {
+    match player_id {
+        PlayerId::One => 0,
+        PlayerId::Two => KEYS.player2_to_move,
+    }
+}
+
+pub fn pending_removal() -> u64 {
+    KEYS.pending_removal
+}